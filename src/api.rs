@@ -0,0 +1,191 @@
+use std::{future::Future, pin::Pin};
+
+use crate::{ApiResult, AsyncClient, params, response};
+
+/// Future returned by [`Proxy6Api`] methods.
+#[cfg(not(target_arch = "wasm32"))]
+pub type ApiFuture<'a, T> = Pin<Box<dyn Future<Output = ApiResult<T>> + Send + 'a>>;
+
+/// Future returned by [`Proxy6Api`] methods, not `Send` on `wasm32` as browser requests are bound to their thread.
+#[cfg(target_arch = "wasm32")]
+pub type ApiFuture<'a, T> = Pin<Box<dyn Future<Output = ApiResult<T>> + 'a>>;
+
+/// API methods of [`AsyncClient`] as an object-safe trait, e.g. to inject a fake API into application code.
+///
+/// Every method mirrors the [`AsyncClient`] method of the same name.
+pub trait Proxy6Api: Send + Sync {
+    fn get_price(&self, params: params::GetPrice) -> ApiFuture<'_, response::GetPrice>;
+
+    fn get_count(&self, params: params::GetCount) -> ApiFuture<'_, response::GetCount>;
+
+    fn get_country(&self, params: params::GetCountry) -> ApiFuture<'_, response::GetCountry>;
+
+    fn get_proxy(&self, params: params::GetProxy) -> ApiFuture<'_, response::GetProxy>;
+
+    fn set_type(&self, params: params::SetType) -> ApiFuture<'_, response::SuccessResponse>;
+
+    fn set_description(
+        &self,
+        params: params::SetDescription,
+    ) -> ApiFuture<'_, response::SetDescription>;
+
+    fn buy(&self, params: params::Buy) -> ApiFuture<'_, response::Buy>;
+
+    fn prolong(&self, params: params::Prolong) -> ApiFuture<'_, response::Prolong>;
+
+    fn delete(&self, params: params::Delete) -> ApiFuture<'_, response::Delete>;
+
+    fn check(&self, params: params::Check) -> ApiFuture<'_, response::Check>;
+
+    fn ip_auth(&self, params: params::IpAuth) -> ApiFuture<'_, response::IpAuth>;
+}
+
+impl Proxy6Api for AsyncClient {
+    fn get_price(&self, params: params::GetPrice) -> ApiFuture<'_, response::GetPrice> {
+        Box::pin(Self::get_price(self, params))
+    }
+
+    fn get_count(&self, params: params::GetCount) -> ApiFuture<'_, response::GetCount> {
+        Box::pin(Self::get_count(self, params))
+    }
+
+    fn get_country(&self, params: params::GetCountry) -> ApiFuture<'_, response::GetCountry> {
+        Box::pin(Self::get_country(self, params))
+    }
+
+    fn get_proxy(&self, params: params::GetProxy) -> ApiFuture<'_, response::GetProxy> {
+        Box::pin(Self::get_proxy(self, params))
+    }
+
+    fn set_type(&self, params: params::SetType) -> ApiFuture<'_, response::SuccessResponse> {
+        Box::pin(Self::set_type(self, params))
+    }
+
+    fn set_description(
+        &self,
+        params: params::SetDescription,
+    ) -> ApiFuture<'_, response::SetDescription> {
+        Box::pin(Self::set_description(self, params))
+    }
+
+    fn buy(&self, params: params::Buy) -> ApiFuture<'_, response::Buy> {
+        Box::pin(Self::buy(self, params))
+    }
+
+    fn prolong(&self, params: params::Prolong) -> ApiFuture<'_, response::Prolong> {
+        Box::pin(Self::prolong(self, params))
+    }
+
+    fn delete(&self, params: params::Delete) -> ApiFuture<'_, response::Delete> {
+        Box::pin(Self::delete(self, params))
+    }
+
+    fn check(&self, params: params::Check) -> ApiFuture<'_, response::Check> {
+        Box::pin(Self::check(self, params))
+    }
+
+    fn ip_auth(&self, params: params::IpAuth) -> ApiFuture<'_, response::IpAuth> {
+        Box::pin(Self::ip_auth(self, params))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::{ProxyCount, ProxyPeriod, error::ApiError, transport::MockTransport};
+
+    const GET_PRICE_RESPONSE: &str = r#"{"status":"yes","user_id":"1","balance":"48.80","currency":"RUB","price":1800,"price_single":0.6,"period":30,"count":100}"#;
+
+    /// Fake answering `get_price` only, as application tests would write it.
+    struct FakeApi;
+
+    impl Proxy6Api for FakeApi {
+        fn get_price(&self, _params: params::GetPrice) -> ApiFuture<'_, response::GetPrice> {
+            Box::pin(async { Ok(serde_json::from_str(GET_PRICE_RESPONSE).unwrap()) })
+        }
+
+        fn get_count(&self, _params: params::GetCount) -> ApiFuture<'_, response::GetCount> {
+            Box::pin(async { Err(ApiError::EmptyResponse) })
+        }
+
+        fn get_country(&self, _params: params::GetCountry) -> ApiFuture<'_, response::GetCountry> {
+            Box::pin(async { Err(ApiError::EmptyResponse) })
+        }
+
+        fn get_proxy(&self, _params: params::GetProxy) -> ApiFuture<'_, response::GetProxy> {
+            Box::pin(async { Err(ApiError::EmptyResponse) })
+        }
+
+        fn set_type(&self, _params: params::SetType) -> ApiFuture<'_, response::SuccessResponse> {
+            Box::pin(async { Err(ApiError::EmptyResponse) })
+        }
+
+        fn set_description(
+            &self,
+            _params: params::SetDescription,
+        ) -> ApiFuture<'_, response::SetDescription> {
+            Box::pin(async { Err(ApiError::EmptyResponse) })
+        }
+
+        fn buy(&self, _params: params::Buy) -> ApiFuture<'_, response::Buy> {
+            Box::pin(async { Err(ApiError::EmptyResponse) })
+        }
+
+        fn prolong(&self, _params: params::Prolong) -> ApiFuture<'_, response::Prolong> {
+            Box::pin(async { Err(ApiError::EmptyResponse) })
+        }
+
+        fn delete(&self, _params: params::Delete) -> ApiFuture<'_, response::Delete> {
+            Box::pin(async { Err(ApiError::EmptyResponse) })
+        }
+
+        fn check(&self, _params: params::Check) -> ApiFuture<'_, response::Check> {
+            Box::pin(async { Err(ApiError::EmptyResponse) })
+        }
+
+        fn ip_auth(&self, _params: params::IpAuth) -> ApiFuture<'_, response::IpAuth> {
+            Box::pin(async { Err(ApiError::EmptyResponse) })
+        }
+    }
+
+    fn get_price_params() -> params::GetPrice {
+        params::GetPrice {
+            count: ProxyCount::new(100).unwrap(),
+            period: ProxyPeriod::new(30).unwrap(),
+            version: None,
+        }
+    }
+
+    async fn total_price(api: &dyn Proxy6Api) -> ApiResult<f64> {
+        Ok(api.get_price(get_price_params()).await?.price.as_f64())
+    }
+
+    #[tokio::test]
+    async fn test_trait_object_with_fake_api() {
+        let api: Box<dyn Proxy6Api> = Box::new(FakeApi);
+
+        let price = total_price(api.as_ref()).await.unwrap();
+        assert!((price - 1800.0).abs() < f64::EPSILON);
+        assert!(matches!(
+            api.get_country(params::GetCountry { version: None }).await,
+            Err(ApiError::EmptyResponse)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_trait_object_with_client() {
+        let transport = MockTransport::new().with_response(200, GET_PRICE_RESPONSE);
+        let client = AsyncClient::builder()
+            .api_key("test-api-key")
+            .transport(transport.clone())
+            .build()
+            .unwrap();
+        let api: std::sync::Arc<dyn Proxy6Api> = std::sync::Arc::new(client);
+
+        let price = total_price(api.as_ref()).await.unwrap();
+        assert!((price - 1800.0).abs() < f64::EPSILON);
+        assert_eq!(transport.requests().len(), 1);
+    }
+}
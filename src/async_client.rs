@@ -1,23 +1,61 @@
+use std::{sync::Arc, time::Duration};
+
 use crate::{
-    ApiResult, ClientBuildError, error,
+    API_KEY_ENV_VAR, API_REQUEST_SPACING, ApiKey, ApiResult, ClientBuildError, MethodName,
+    ProxyVersion, RequestHook,
+    client_core::{ClientCore, CoreOptions},
+    error, mask_api_key,
     method::{self, ApiMethod},
-    params, response,
+    params, parse_response, response,
+    time::{self, Instant},
+    transport::AsyncTransport,
 };
 
-const DEFAULT_BASE_URL: &str = "https://px6.link";
-
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct AsyncClient {
-    base_url: String,
-    requester: reqwest::Client,
-    api_key: String,
+    core: ClientCore<dyn AsyncTransport>,
+    concurrency_limiter: Option<Arc<tokio::sync::Semaphore>>,
+}
+
+impl std::fmt::Debug for AsyncClient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AsyncClient")
+            .field("base_url", &self.core.base_url.as_str())
+            .field("transport", &self.core.transport)
+            .field("api_key", &mask_api_key(self.core.api_key.as_str()))
+            .field("max_retries", &self.core.max_retries)
+            .field("retry_backoff", &self.core.retry_backoff)
+            .field("rate_limiter", &self.core.rate_limiter)
+            .field("concurrency_limiter", &self.concurrency_limiter)
+            .field("default_version", &self.core.default_version)
+            .field("country_cache", &self.core.country_cache)
+            .field("max_url_length", &self.core.max_url_length)
+            .field("on_request", &self.core.on_request)
+            .finish()
+    }
 }
 
 #[derive(Default, Debug, Clone)]
 pub struct AsyncClientBuilder {
     base_url: Option<String>,
-    api_key: Option<String>,
+    api_key: Option<ApiKey>,
+    #[cfg(feature = "reqwest")]
     requester: Option<reqwest::Client>,
+    transport: Option<Arc<dyn AsyncTransport>>,
+    max_retries: Option<u32>,
+    retry_backoff: Option<Duration>,
+    rate_limit: Option<(u32, Duration)>,
+    max_concurrent: Option<usize>,
+    #[cfg(all(feature = "reqwest", not(target_arch = "wasm32")))]
+    timeout: Option<Duration>,
+    #[cfg(feature = "reqwest")]
+    default_headers: Option<reqwest::header::HeaderMap>,
+    #[cfg(all(feature = "reqwest", not(target_arch = "wasm32")))]
+    via_proxy: Option<reqwest::Proxy>,
+    default_version: Option<ProxyVersion>,
+    cache_countries: Option<Duration>,
+    max_url_length: Option<usize>,
+    on_request: Option<RequestHook>,
 }
 
 impl AsyncClientBuilder {
@@ -26,41 +64,233 @@ impl AsyncClientBuilder {
         Self::default()
     }
 
+    /// Sets the API base URL, defaults to [`crate::DEFAULT_BASE_URL`].
     #[must_use]
     pub fn base_url(mut self, base_url: impl Into<String>) -> Self {
         self.base_url = Some(base_url.into());
         self
     }
 
+    /// Sets the API key, it is validated when the client is built, see [`ApiKey::new`].
     #[must_use]
-    pub fn api_key(mut self, api_key: impl Into<String>) -> Self {
+    pub fn api_key(mut self, api_key: impl Into<ApiKey>) -> Self {
         self.api_key = Some(api_key.into());
         self
     }
 
+    /// Reads the API key from the given environment variable, unset or empty variable leaves the key unset.
+    #[must_use]
+    pub fn api_key_from_env(mut self, var: &str) -> Self {
+        self.api_key = std::env::var(var)
+            .ok()
+            .filter(|api_key| !api_key.is_empty())
+            .map(ApiKey::from);
+        self
+    }
+
+    /// Reads the API key from the [`API_KEY_ENV_VAR`] environment variable.
+    #[must_use]
+    pub fn api_key_from_default_env(self) -> Self {
+        self.api_key_from_env(API_KEY_ENV_VAR)
+    }
+
+    /// Sets the HTTP client, it takes precedence over [`Self::timeout`], [`Self::default_headers`] and [`Self::via_proxy`].
     #[must_use]
+    #[cfg(feature = "reqwest")]
     pub fn requester(mut self, requester: reqwest::Client) -> Self {
         self.requester = Some(requester);
         self
     }
 
+    /// Sets the transport sending requests instead of `reqwest`, it takes precedence over [`Self::requester`].
+    ///
+    /// Required without the `reqwest` feature.
+    #[must_use]
+    pub fn transport(mut self, transport: impl AsyncTransport + 'static) -> Self {
+        self.transport = Some(Arc::new(transport));
+        self
+    }
+
+    /// Sets how many times a request is retried after [`error::ApiError::TooManyRequests`].
+    ///
+    /// Only reads are retried, see [`ApiMethod::is_idempotent`]: `buy`, `prolong` and other writes fail right away,
+    /// as a retry could apply them twice. Retries are disabled by default.
+    #[must_use]
+    pub const fn max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = Some(max_retries);
+        self
+    }
+
+    /// Sets the delay before the first retry, it is doubled on every next attempt.
+    ///
+    /// Defaults to 1 second.
+    #[must_use]
+    pub const fn retry_backoff(mut self, retry_backoff: Duration) -> Self {
+        self.retry_backoff = Some(retry_backoff);
+        self
+    }
+
+    /// Limits the client to `requests` per `per` interval, the API allows 3 requests per second.
+    ///
+    /// The limiter is shared between all clones of the built client.
+    #[must_use]
+    pub const fn rate_limit(mut self, requests: u32, per: Duration) -> Self {
+        self.rate_limit = Some((requests, per));
+        self
+    }
+
+    /// Limits the client to `max_concurrent` requests in flight at once, other requests wait for a free slot.
+    ///
+    /// Complements [`Self::rate_limit`], the limit is shared between all clones of the built client.
+    #[must_use]
+    pub const fn max_concurrent(mut self, max_concurrent: usize) -> Self {
+        self.max_concurrent = Some(max_concurrent);
+        self
+    }
+
+    /// Sets the total timeout of every request.
+    ///
+    /// Only applies when the builder creates its own HTTP client, see [`Self::requester`].
+    /// Not available on `wasm32`, where `reqwest` has no client-wide timeout.
+    #[must_use]
+    #[cfg(all(feature = "reqwest", not(target_arch = "wasm32")))]
+    pub const fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Sets headers sent with every request, e.g. custom `User-Agent`.
+    ///
+    /// Only applies when the builder creates its own HTTP client, see [`Self::requester`].
+    #[must_use]
+    #[cfg(feature = "reqwest")]
+    pub fn default_headers(mut self, headers: reqwest::header::HeaderMap) -> Self {
+        self.default_headers = Some(headers);
+        self
+    }
+
+    /// Sends API requests through the proxy, e.g. when `px6.link` is not reachable directly.
+    ///
+    /// Only applies when the builder creates its own HTTP client, see [`Self::requester`].
+    /// Not available on `wasm32`, where the browser decides how requests are routed.
+    #[must_use]
+    #[cfg(all(feature = "reqwest", not(target_arch = "wasm32")))]
+    pub fn via_proxy(mut self, proxy: reqwest::Proxy) -> Self {
+        self.via_proxy = Some(proxy);
+        self
+    }
+
+    /// Sets the proxy version used by `get_price`, `get_count`, `get_country` and `buy` when params have no version.
+    ///
+    /// Version explicitly set in params always takes precedence.
+    #[must_use]
+    pub const fn default_version(mut self, version: ProxyVersion) -> Self {
+        self.default_version = Some(version);
+        self
+    }
+
+    /// Caches `get_country` responses per proxy version for `ttl`, the list of countries rarely changes.
+    ///
+    /// Calls within `ttl` are answered without a request. The cache is shared between all clones of the built client.
+    #[must_use]
+    pub const fn cache_countries(mut self, ttl: Duration) -> Self {
+        self.cache_countries = Some(ttl);
+        self
+    }
+
+    /// Sets the longest request URL the client sends, longer requests fail with [`error::ApiError::RequestTooLong`].
+    ///
+    /// Defaults to 8000 characters.
+    #[must_use]
+    pub const fn max_url_length(mut self, max_url_length: usize) -> Self {
+        self.max_url_length = Some(max_url_length);
+        self
+    }
+
+    /// Sets a callback invoked after every API call with the method name, elapsed time and outcome,
+    /// e.g. to record metrics. Retries of a call are reported once, as part of its elapsed time.
+    #[must_use]
+    pub fn on_request(
+        mut self,
+        hook: impl Fn(MethodName, Duration, Result<(), &error::ApiError>) + Send + Sync + 'static,
+    ) -> Self {
+        self.on_request = Some(RequestHook::new(hook));
+        self
+    }
+
     /// Builds a new client.
     ///
     /// # Errors
+    /// - [`ClientBuildError::InvalidBaseUrl`] if the base URL is not a valid http(s) URL.
     /// - [`ClientBuildError::ApiKeyMustBeSet`] if the API key is not set.
+    /// - [`ClientBuildError::InvalidApiKey`] if the API key is empty or not URL-safe.
+    /// - [`ClientBuildError::RateLimitTooLow`] if the rate limit allows zero requests.
+    /// - [`ClientBuildError::ConcurrencyLimitTooLow`] if the concurrency limit allows zero requests.
+    /// - [`ClientBuildError::TransportMustBeSet`] if no transport is set without the `reqwest` feature.
+    /// - [`ClientBuildError::ReqwestError`] if the HTTP client cannot be built.
     pub fn build(self) -> Result<AsyncClient, ClientBuildError> {
-        let base_url = self
-            .base_url
-            .unwrap_or_else(|| DEFAULT_BASE_URL.to_string());
-        let api_key = self.api_key.ok_or(ClientBuildError::ApiKeyMustBeSet)?;
-        let requester = self.requester.unwrap_or_default();
+        let transport = self.build_transport()?;
+        let core = ClientCore::new(
+            transport,
+            CoreOptions {
+                base_url: self.base_url,
+                api_key: self.api_key,
+                max_retries: self.max_retries,
+                retry_backoff: self.retry_backoff,
+                rate_limit: self.rate_limit,
+                default_version: self.default_version,
+                cache_countries: self.cache_countries,
+                max_url_length: self.max_url_length,
+                on_request: self.on_request,
+            },
+        )?;
+        let concurrency_limiter = match self.max_concurrent {
+            Some(0) => return Err(ClientBuildError::ConcurrencyLimitTooLow),
+            Some(permits) => Some(Arc::new(tokio::sync::Semaphore::new(permits))),
+            None => None,
+        };
 
         Ok(AsyncClient {
-            base_url,
-            requester,
-            api_key,
+            core,
+            concurrency_limiter,
         })
     }
+
+    fn build_transport(&self) -> Result<Arc<dyn AsyncTransport>, ClientBuildError> {
+        let Some(transport) = &self.transport else {
+            #[cfg(feature = "reqwest")]
+            return self.build_requester();
+            #[cfg(not(feature = "reqwest"))]
+            return Err(ClientBuildError::TransportMustBeSet);
+        };
+
+        Ok(Arc::clone(transport))
+    }
+
+    #[cfg(feature = "reqwest")]
+    fn build_requester(&self) -> Result<Arc<dyn AsyncTransport>, ClientBuildError> {
+        if let Some(requester) = &self.requester {
+            return Ok(Arc::new(requester.clone()));
+        }
+
+        let mut requester = reqwest::Client::builder();
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(timeout) = self.timeout {
+            requester = requester.timeout(timeout);
+        }
+        if let Some(headers) = &self.default_headers {
+            requester = requester.default_headers(headers.clone());
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(proxy) = &self.via_proxy {
+            requester = requester.proxy(proxy.clone());
+        }
+
+        match requester.build() {
+            Ok(requester) => Ok(Arc::new(requester)),
+            Err(err) => Err(ClientBuildError::ReqwestError { source: err }),
+        }
+    }
 }
 
 impl AsyncClient {
@@ -69,56 +299,84 @@ impl AsyncClient {
         AsyncClientBuilder::new()
     }
 
+    /// Returns the exact URL the client would request for the method, without sending it.
+    ///
+    /// The URL contains the API key, redact it before logging.
+    #[must_use]
+    pub fn build_url(&self, method: &ApiMethod) -> String {
+        self.core.build_url(method)
+    }
+
     async fn get_request_with_params<TResponse: serde::de::DeserializeOwned>(
         &self,
         method: &method::ApiMethod,
     ) -> Result<TResponse, error::ApiError> {
-        let url = format!(
-            "{}/api/{}/{}?{}",
-            self.base_url,
-            self.api_key,
-            method,
-            method.get_params().to_query_string()
-        );
-
-        let response = self
-            .requester
-            .get(url)
-            .send()
+        self.get_raw_request_with_params(method)
             .await
-            .map_err(|err| error::ApiError::ReqwestError { source: err })?;
+            .map(|raw| raw.data)
+    }
 
-        let response_status = response.status();
-        let response_text = response
-            .text()
-            .await
-            .map_err(|err| error::ApiError::ReqwestError { source: err })?;
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "proxy6_request", skip_all, fields(method = %method))
+    )]
+    async fn get_raw_request_with_params<TResponse: serde::de::DeserializeOwned>(
+        &self,
+        method: &method::ApiMethod,
+    ) -> Result<response::RawResponse<TResponse>, error::ApiError> {
+        let method = self.core.with_defaults(method);
+        let started = Instant::now();
+        let mut attempt = 0;
 
-        if response_status == reqwest::StatusCode::TOO_MANY_REQUESTS {
-            return Err(error::ApiError::TooManyRequests {
-                response: response_text,
-            });
-        }
+        let result = loop {
+            let result = self.send_request(&method).await;
 
-        if let Some(possible_error) =
-            error::DocumentedErrorCode::parse_from_response_body(&response_text)
-        {
-            return Err(error::ApiError::DocumentedError {
-                response: response_text,
-                code: possible_error,
-            });
-        }
+            #[cfg(feature = "tracing")]
+            if let Err(err) = &result {
+                tracing::warn!(error = %self.core.redact(&method, &err.to_string()), attempt, "request failed");
+            }
+
+            match self.core.retry_delay(&method, &result, attempt) {
+                Some(delay) => {
+                    time::sleep(delay).await;
+                    attempt += 1;
+                }
+                None => break result,
+            }
+        };
+
+        self.core.report(&method, started, &result);
+
+        result
+    }
+
+    async fn send_request<TResponse: serde::de::DeserializeOwned>(
+        &self,
+        method: &method::ApiMethod,
+    ) -> Result<response::RawResponse<TResponse>, error::ApiError> {
+        let url = self.core.request_url(method)?;
 
-        if !response_status.is_success() {
-            return Err(error::ApiError::UnknownError {
-                response: response_text,
-            });
+        // The permit is held until the response is received, the semaphore is never closed.
+        let _permit = match &self.concurrency_limiter {
+            Some(limiter) => limiter.acquire().await.ok(),
+            None => None,
+        };
+
+        if let Some(delay) = self.core.rate_limit_delay() {
+            time::sleep(delay).await;
         }
 
-        serde_json::from_str(&response_text).map_err(|err| error::ApiError::SuccessButCannotParse {
-            source: err,
-            response: response_text,
-        })
+        #[cfg(feature = "tracing")]
+        tracing::debug!(url = %self.core.redact(method, &url), "sending request");
+
+        let response = self
+            .core
+            .transport
+            .get(&url)
+            .await
+            .map_err(|err| self.core.transport_error(err))?;
+
+        parse_response(response)
     }
 
     /// Get information about the cost of the order, depending on the version, period and number of proxy.
@@ -144,8 +402,94 @@ impl AsyncClient {
     /// # Errors
     /// Any error can be thrown (see [`error::ApiError`])
     pub async fn get_country(&self, params: params::GetCountry) -> ApiResult<response::GetCountry> {
-        self.get_request_with_params(&ApiMethod::GetCountry(params))
+        let version = self.core.country_cache_key(&params);
+        if let Some(cached) = self.core.cached_country(version.as_ref()) {
+            return Ok(cached);
+        }
+
+        let response: response::GetCountry = self
+            .get_request_with_params(&ApiMethod::GetCountry(params))
+            .await?;
+        self.core.cache_country(version, &response);
+
+        Ok(response)
+    }
+
+    /// Get how many proxies of `version` can be bought in every available country.
+    ///
+    /// Sends [`Self::get_country`] and then [`Self::get_count`] per country. Without [`AsyncClientBuilder::rate_limit`]
+    /// the requests are spaced to stay within 3 requests per second.
+    ///
+    /// # Errors
+    /// Any error can be thrown (see [`error::ApiError`])
+    pub async fn country_availability(
+        &self,
+        version: Option<ProxyVersion>,
+    ) -> ApiResult<std::collections::HashMap<crate::Country, usize>> {
+        let countries = self
+            .get_country(params::GetCountry {
+                version: version.clone(),
+            })
+            .await?;
+        let mut availability = std::collections::HashMap::new();
+
+        for country in countries {
+            if self.core.rate_limiter.is_none() {
+                time::sleep(API_REQUEST_SPACING).await;
+            }
+
+            let response = self
+                .get_count(params::GetCount {
+                    country: country.clone(),
+                    version: version.clone(),
+                })
+                .await?;
+            availability.insert(country, response.count);
+        }
+
+        Ok(availability)
+    }
+
+    /// Get your account id, balance and currency.
+    ///
+    /// The API has no dedicated method for this, so it piggybacks on `getcountry` which has no side effects.
+    /// The request is always sent, even with [`AsyncClientBuilder::cache_countries`].
+    ///
+    /// # Errors
+    /// Any error can be thrown (see [`error::ApiError`])
+    pub async fn balance(&self) -> ApiResult<response::Balance> {
+        let response: response::GetCountry = self
+            .get_request_with_params(&ApiMethod::GetCountry(params::GetCountry { version: None }))
+            .await?;
+
+        Ok(response::Balance {
+            user_id: response.user_id,
+            balance: response.balance,
+            currency: response.currency,
+        })
+    }
+
+    /// Check that the API is reachable and accepts the key, e.g. to validate configuration at startup.
+    ///
+    /// Sends `getcountry` without version, the cheapest authenticated request, bypassing [`AsyncClientBuilder::cache_countries`]. A rejected key is
+    /// [`response::Ping::BadKey`] rather than an error, so it can be told apart from an unreachable API.
+    ///
+    /// # Errors
+    /// Any error except [`error::DocumentedErrorCode::Key`] can be thrown (see [`error::ApiError`])
+    pub async fn ping(&self) -> ApiResult<response::Ping> {
+        match self
+            .get_request_with_params::<response::GetCountry>(&ApiMethod::GetCountry(
+                params::GetCountry { version: None },
+            ))
             .await
+        {
+            Ok(_) => Ok(response::Ping::Authorized),
+            Err(error::ApiError::DocumentedError {
+                code: error::DocumentedErrorCode::Key,
+                ..
+            }) => Ok(response::Ping::BadKey),
+            Err(error) => Err(error),
+        }
     }
 
     /// Get the list of your proxies.
@@ -157,6 +501,92 @@ impl AsyncClient {
             .await
     }
 
+    /// Same as [`Self::get_proxy`], but a proxy that fails to parse does not fail the whole page.
+    ///
+    /// # Errors
+    /// Any error can be thrown (see [`error::ApiError`])
+    pub async fn get_proxy_lenient(
+        &self,
+        params: params::GetProxy,
+    ) -> ApiResult<response::GetProxyLenient> {
+        self.get_request_with_params(&ApiMethod::GetProxy(params))
+            .await
+    }
+
+    /// Get the list of your proxies, keeping only the ones matching `predicate`.
+    ///
+    /// The API can filter only by `state` and `description` (see [`params::GetProxy`]), so other filters,
+    /// e.g. by [`crate::ProxyType`], are applied here to the received page. `list_count` is left as returned by the API.
+    ///
+    /// # Errors
+    /// Any error can be thrown (see [`error::ApiError`])
+    pub async fn get_proxy_filtered(
+        &self,
+        params: params::GetProxy,
+        predicate: impl Fn(&crate::Proxy) -> bool,
+    ) -> ApiResult<response::GetProxy> {
+        let mut response = self.get_proxy(params).await?;
+        response.list.retain(|proxy| predicate(proxy));
+
+        Ok(response)
+    }
+
+    /// Get the list of your proxies in any of `states`, the API accepts only one state per request.
+    ///
+    /// Sends `base` once per state and merges the lists, proxies returned for several states are kept once.
+    ///
+    /// # Errors
+    /// Any error can be thrown (see [`error::ApiError`])
+    pub async fn get_proxy_multi(
+        &self,
+        states: &[crate::ProxyStatus],
+        base: &params::GetProxy,
+    ) -> ApiResult<Vec<crate::Proxy>> {
+        let mut seen = std::collections::HashSet::new();
+        let mut proxies = Vec::new();
+
+        for state in states {
+            let response = self
+                .get_proxy(params::GetProxy {
+                    state: Some(state.clone()),
+                    ..base.clone()
+                })
+                .await?;
+
+            proxies.extend(
+                response
+                    .list
+                    .into_iter()
+                    .filter(|proxy| seen.insert(proxy.id.clone())),
+            );
+        }
+
+        Ok(proxies)
+    }
+
+    /// Get all your proxies, requesting pages one by one until every proxy is collected.
+    ///
+    /// Pagination starts from `params.page` (first page by default) with `params.limit` proxies per page (server default if not set).
+    ///
+    /// # Errors
+    /// Any error can be thrown (see [`error::ApiError`])
+    pub async fn get_all_proxies(&self, params: params::GetProxy) -> ApiResult<Vec<crate::Proxy>> {
+        let mut params = params.first_page();
+        let mut proxies = Vec::new();
+
+        loop {
+            let response = self.get_proxy(params.clone()).await?;
+            let received = response.list.len();
+            let remaining = response.remaining(proxies.len() + received);
+            proxies.extend(response.list);
+
+            match params.next_page(received, remaining) {
+                Some(next_page) => params = next_page,
+                None => return Ok(proxies),
+            }
+        }
+    }
+
     /// Change the type (protocol) of your proxy.
     ///
     /// # Errors
@@ -168,6 +598,26 @@ impl AsyncClient {
             .await
     }
 
+    /// Same as [`Self::set_type`], but proxies already having the requested type are not an error.
+    ///
+    /// Returns `None` when the API reports [`error::DocumentedErrorCode::Unknown`], which it does if nothing had to change.
+    ///
+    /// # Errors
+    /// Any error except [`error::DocumentedErrorCode::Unknown`] can be thrown (see [`error::ApiError`])
+    pub async fn set_type_idempotent(
+        &self,
+        params: params::SetType,
+    ) -> ApiResult<Option<response::SuccessResponse>> {
+        match self.set_type(params).await {
+            Ok(response) => Ok(Some(response)),
+            Err(error::ApiError::DocumentedError {
+                code: error::DocumentedErrorCode::Unknown,
+                ..
+            }) => Ok(None),
+            Err(error) => Err(error),
+        }
+    }
+
     /// Update technical comments in the proxy list that was added when buying.
     ///
     /// # Errors
@@ -188,6 +638,34 @@ impl AsyncClient {
         self.get_request_with_params(&ApiMethod::Buy(params)).await
     }
 
+    /// Purchase proxies in several orders of at most `chunk_size` proxies, e.g. to stay under server limits.
+    ///
+    /// Orders are sent one by one and stop on the first failed one.
+    ///
+    /// # Errors
+    /// [`error::BatchError`] with the orders completed before the failed one, any error can be its source (see [`error::ApiError`])
+    pub async fn buy_batched(
+        &self,
+        params: params::Buy,
+        chunk_size: crate::ProxyCount,
+    ) -> Result<response::Batch<response::Buy>, error::BatchError<response::Buy>> {
+        let mut batch = response::Batch::default();
+
+        for chunk in params.chunks(chunk_size) {
+            match self.buy(chunk).await {
+                Ok(response) => batch.responses.push(response),
+                Err(source) => {
+                    return Err(error::BatchError {
+                        completed: batch,
+                        source,
+                    });
+                }
+            }
+        }
+
+        Ok(batch)
+    }
+
     /// Extend existing proxies.
     ///
     /// # Errors
@@ -197,6 +675,70 @@ impl AsyncClient {
             .await
     }
 
+    /// Extend existing proxies in several requests of at most `chunk_size` ids, so the request URL stays short.
+    ///
+    /// Requests are sent one by one and stop on the first failed one.
+    ///
+    /// # Errors
+    /// [`error::BatchError`] with the requests completed before the failed one, any error can be its source (see [`error::ApiError`])
+    pub async fn prolong_batched(
+        &self,
+        params: params::Prolong,
+        chunk_size: crate::ProxyCount,
+    ) -> Result<response::Batch<response::Prolong>, error::BatchError<response::Prolong>> {
+        let mut batch = response::Batch::default();
+
+        for chunk in params.chunks(chunk_size) {
+            match self.prolong(chunk).await {
+                Ok(response) => batch.responses.push(response),
+                Err(source) => {
+                    return Err(error::BatchError {
+                        completed: batch,
+                        source,
+                    });
+                }
+            }
+        }
+
+        Ok(batch)
+    }
+
+    /// Extend every proxy that is still valid but expires within `within` from now, see [`Self::prolong_batched`].
+    ///
+    /// Ids are sent in requests of at most 200, so large accounts stay under the URL length limit.
+    /// Returns an empty batch without sending a prolong request if no proxy expires within `within`.
+    ///
+    /// # Errors
+    /// [`error::BatchError`] with the requests completed before the failed one, any error can be its source (see [`error::ApiError`])
+    pub async fn prolong_expiring(
+        &self,
+        within: Duration,
+        period: crate::ProxyPeriod,
+    ) -> Result<response::Batch<response::Prolong>, error::BatchError<response::Prolong>> {
+        let now = std::time::SystemTime::now();
+        let ids: Vec<_> = self
+            .get_all_proxies(params::GetProxy::builder().build())
+            .await
+            .map_err(|source| error::BatchError {
+                completed: response::Batch::default(),
+                source,
+            })?
+            .into_iter()
+            .filter(|proxy| proxy.expires_within(within, now))
+            .map(|proxy| proxy.id)
+            .collect();
+
+        self.prolong_batched(
+            params::Prolong {
+                period,
+                ids,
+                keyed_list: false,
+            },
+            crate::ProxyCount::PROLONG_CHUNK,
+        )
+        .await
+    }
+
     /// Delete existing proxies.
     ///
     /// # Errors
@@ -206,6 +748,23 @@ impl AsyncClient {
             .await
     }
 
+    /// Same as [`Self::delete`], but proxies that are already gone are not an error, so a cleanup can be safely repeated.
+    ///
+    /// Returns the amount of deleted proxies, `0` if the API reports nothing found.
+    ///
+    /// # Errors
+    /// Any error except [`error::DocumentedErrorCode::NotFound`] can be thrown (see [`error::ApiError`])
+    pub async fn delete_idempotent(&self, params: params::Delete) -> ApiResult<usize> {
+        match self.delete(params).await {
+            Ok(response) => Ok(response.count),
+            Err(error::ApiError::DocumentedError {
+                code: error::DocumentedErrorCode::NotFound,
+                ..
+            }) => Ok(0),
+            Err(error) => Err(error),
+        }
+    }
+
     /// Check the validity of the proxy.
     ///
     /// # Errors
@@ -219,41 +778,237 @@ impl AsyncClient {
     ///
     /// # Errors
     /// Any error can be thrown (see [`error::ApiError`])
-    pub async fn ip_auth(&self, params: params::IpAuth) -> ApiResult<response::SuccessResponse> {
-        self.get_request_with_params(&ApiMethod::IpAuth(params))
-            .await
-    }
-}
+    pub async fn ip_auth(&self, params: params::IpAuth) -> ApiResult<response::IpAuth> {
+        let ip = params.ip.clone();
+        let mut response: response::IpAuth = self
+            .get_request_with_params(&ApiMethod::IpAuth(params))
+            .await?;
+        response.ip = Some(ip);
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+        Ok(response)
+    }
 
-    #[test]
-    fn test_builder_new() {
-        let builder = AsyncClientBuilder::new();
-        assert_eq!(builder.base_url, None);
-        assert_eq!(builder.api_key, None);
-        assert!(builder.requester.is_none());
+    /// Call an API method this crate does not model yet, e.g. a newly added one, and get its JSON response.
+    ///
+    /// Params are percent-encoded, an empty value sends the key alone (e.g. `nokey`).
+    /// The response goes through the same retries and error classification as the other methods.
+    ///
+    /// # Errors
+    /// Any error can be thrown (see [`error::ApiError`])
+    pub async fn call_raw(
+        &self,
+        method: &str,
+        params: &[(&str, &str)],
+    ) -> ApiResult<serde_json::Value> {
+        self.get_request_with_params(&ApiMethod::Raw(method::RawParams {
+            method: method.to_string(),
+            params: params
+                .iter()
+                .map(|(key, value)| ((*key).to_string(), (*value).to_string()))
+                .collect(),
+        }))
+        .await
     }
 
-    #[test]
-    fn test_builder_base_url() {
-        let builder = AsyncClientBuilder::new().base_url("https://custom.example.com");
-        assert_eq!(
-            builder.base_url,
-            Some("https://custom.example.com".to_string())
-        );
-        assert_eq!(builder.api_key, None);
-        assert!(builder.requester.is_none());
+    /// Same as [`Self::get_price`], but also returns the response body.
+    ///
+    /// # Errors
+    /// Any error can be thrown (see [`error::ApiError`])
+    pub async fn get_price_raw(
+        &self,
+        params: params::GetPrice,
+    ) -> ApiResult<response::RawResponse<response::GetPrice>> {
+        self.get_raw_request_with_params(&ApiMethod::GetPrice(params))
+            .await
     }
 
-    #[test]
-    fn test_builder_api_key() {
-        let builder = AsyncClientBuilder::new().api_key("test-api-key");
-        assert_eq!(builder.base_url, None);
-        assert_eq!(builder.api_key, Some("test-api-key".to_string()));
-        assert!(builder.requester.is_none());
+    /// Same as [`Self::get_count`], but also returns the response body.
+    ///
+    /// # Errors
+    /// Any error can be thrown (see [`error::ApiError`])
+    pub async fn get_count_raw(
+        &self,
+        params: params::GetCount,
+    ) -> ApiResult<response::RawResponse<response::GetCount>> {
+        self.get_raw_request_with_params(&ApiMethod::GetCount(params))
+            .await
+    }
+
+    /// Same as [`Self::get_country`], but also returns the response body.
+    ///
+    /// # Errors
+    /// Any error can be thrown (see [`error::ApiError`])
+    pub async fn get_country_raw(
+        &self,
+        params: params::GetCountry,
+    ) -> ApiResult<response::RawResponse<response::GetCountry>> {
+        self.get_raw_request_with_params(&ApiMethod::GetCountry(params))
+            .await
+    }
+
+    /// Same as [`Self::get_proxy`], but also returns the response body.
+    ///
+    /// # Errors
+    /// Any error can be thrown (see [`error::ApiError`])
+    pub async fn get_proxy_raw(
+        &self,
+        params: params::GetProxy,
+    ) -> ApiResult<response::RawResponse<response::GetProxy>> {
+        self.get_raw_request_with_params(&ApiMethod::GetProxy(params))
+            .await
+    }
+
+    /// Same as [`Self::set_type`], but also returns the response body.
+    ///
+    /// # Errors
+    /// Any error can be thrown (see [`error::ApiError`])
+    pub async fn set_type_raw(
+        &self,
+        params: params::SetType,
+    ) -> ApiResult<response::RawResponse<response::SuccessResponse>> {
+        self.get_raw_request_with_params(&ApiMethod::SetType(params))
+            .await
+    }
+
+    /// Same as [`Self::set_description`], but also returns the response body.
+    ///
+    /// # Errors
+    /// Any error can be thrown (see [`error::ApiError`])
+    pub async fn set_description_raw(
+        &self,
+        params: params::SetDescription,
+    ) -> ApiResult<response::RawResponse<response::SetDescription>> {
+        self.get_raw_request_with_params(&ApiMethod::SetDescription(params))
+            .await
+    }
+
+    /// Same as [`Self::buy`], but also returns the response body.
+    ///
+    /// # Errors
+    /// Any error can be thrown (see [`error::ApiError`])
+    pub async fn buy_raw(
+        &self,
+        params: params::Buy,
+    ) -> ApiResult<response::RawResponse<response::Buy>> {
+        self.get_raw_request_with_params(&ApiMethod::Buy(params))
+            .await
+    }
+
+    /// Same as [`Self::prolong`], but also returns the response body.
+    ///
+    /// # Errors
+    /// Any error can be thrown (see [`error::ApiError`])
+    pub async fn prolong_raw(
+        &self,
+        params: params::Prolong,
+    ) -> ApiResult<response::RawResponse<response::Prolong>> {
+        self.get_raw_request_with_params(&ApiMethod::Prolong(params))
+            .await
+    }
+
+    /// Same as [`Self::delete`], but also returns the response body.
+    ///
+    /// # Errors
+    /// Any error can be thrown (see [`error::ApiError`])
+    pub async fn delete_raw(
+        &self,
+        params: params::Delete,
+    ) -> ApiResult<response::RawResponse<response::Delete>> {
+        self.get_raw_request_with_params(&ApiMethod::Delete(params))
+            .await
+    }
+
+    /// Same as [`Self::check`], but also returns the response body.
+    ///
+    /// # Errors
+    /// Any error can be thrown (see [`error::ApiError`])
+    pub async fn check_raw(
+        &self,
+        params: params::Check,
+    ) -> ApiResult<response::RawResponse<response::Check>> {
+        self.get_raw_request_with_params(&ApiMethod::Check(params))
+            .await
+    }
+
+    /// Same as [`Self::ip_auth`], but also returns the response body.
+    ///
+    /// # Errors
+    /// Any error can be thrown (see [`error::ApiError`])
+    pub async fn ip_auth_raw(
+        &self,
+        params: params::IpAuth,
+    ) -> ApiResult<response::RawResponse<response::IpAuth>> {
+        let ip = params.ip.clone();
+        let mut response: response::RawResponse<response::IpAuth> = self
+            .get_raw_request_with_params(&ApiMethod::IpAuth(params))
+            .await?;
+        response.data.ip = Some(ip);
+
+        Ok(response)
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "reqwest")]
+#[allow(
+    clippy::significant_drop_tightening,
+    reason = "mock server must outlive the client"
+)]
+mod tests {
+    use super::*;
+    use crate::{client_core::DEFAULT_RETRY_BACKOFF, transport};
+
+    #[test]
+    fn test_builder_new() {
+        let builder = AsyncClientBuilder::new();
+        assert_eq!(builder.base_url, None);
+        assert_eq!(builder.api_key, None);
+        assert!(builder.requester.is_none());
+    }
+
+    #[test]
+    fn test_builder_base_url() {
+        let builder = AsyncClientBuilder::new().base_url("https://custom.example.com");
+        assert_eq!(
+            builder.base_url,
+            Some("https://custom.example.com".to_string())
+        );
+        assert_eq!(builder.api_key, None);
+        assert!(builder.requester.is_none());
+    }
+
+    #[test]
+    fn test_builder_api_key() {
+        let builder = AsyncClientBuilder::new().api_key("test-api-key");
+        assert_eq!(builder.base_url, None);
+        assert_eq!(builder.api_key, Some(ApiKey::from("test-api-key")));
+        assert!(builder.requester.is_none());
+    }
+
+    #[test]
+    fn test_builder_api_key_from_env() {
+        // SAFETY: the variable is unique to this test
+        unsafe { std::env::set_var("PROXY6_TEST_ASYNC_API_KEY", "env-api-key") };
+        let builder = AsyncClientBuilder::new().api_key_from_env("PROXY6_TEST_ASYNC_API_KEY");
+        assert_eq!(builder.api_key, Some(ApiKey::from("env-api-key")));
+    }
+
+    #[test]
+    fn test_builder_api_key_from_empty_env_error() {
+        // SAFETY: the variable is unique to this test
+        unsafe { std::env::set_var("PROXY6_TEST_ASYNC_EMPTY_API_KEY", "") };
+        let result = AsyncClientBuilder::new()
+            .api_key_from_env("PROXY6_TEST_ASYNC_EMPTY_API_KEY")
+            .build();
+        assert!(matches!(result, Err(ClientBuildError::ApiKeyMustBeSet)));
+    }
+
+    #[test]
+    fn test_builder_api_key_from_unset_env_error() {
+        let result = AsyncClientBuilder::new()
+            .api_key_from_env("PROXY6_TEST_ASYNC_UNSET_API_KEY")
+            .build();
+        assert!(matches!(result, Err(ClientBuildError::ApiKeyMustBeSet)));
     }
 
     #[test]
@@ -265,6 +1020,43 @@ mod tests {
         assert!(builder.requester.is_some());
     }
 
+    #[test]
+    fn test_builder_max_retries() {
+        let builder = AsyncClientBuilder::new().max_retries(3);
+        assert_eq!(builder.max_retries, Some(3));
+        assert_eq!(builder.retry_backoff, None);
+    }
+
+    #[test]
+    fn test_builder_retry_backoff() {
+        let builder = AsyncClientBuilder::new().retry_backoff(Duration::from_millis(100));
+        assert_eq!(builder.max_retries, None);
+        assert_eq!(builder.retry_backoff, Some(Duration::from_millis(100)));
+    }
+
+    #[test]
+    fn test_builder_rate_limit() {
+        let builder = AsyncClientBuilder::new().rate_limit(3, Duration::from_secs(1));
+        assert_eq!(builder.rate_limit, Some((3, Duration::from_secs(1))));
+    }
+
+    #[test]
+    fn test_builder_timeout() {
+        let builder = AsyncClientBuilder::new().timeout(Duration::from_secs(5));
+        assert_eq!(builder.timeout, Some(Duration::from_secs(5)));
+    }
+
+    #[test]
+    fn test_builder_default_headers() {
+        let mut headers = reqwest::header::HeaderMap::new();
+        headers.insert(
+            reqwest::header::USER_AGENT,
+            reqwest::header::HeaderValue::from_static("proxy6-test"),
+        );
+        let builder = AsyncClientBuilder::new().default_headers(headers.clone());
+        assert_eq!(builder.default_headers, Some(headers));
+    }
+
     #[test]
     fn test_builder_build_success() {
         let client = AsyncClientBuilder::new()
@@ -273,8 +1065,8 @@ mod tests {
             .build()
             .unwrap();
 
-        assert_eq!(client.base_url, "https://custom.example.com");
-        assert_eq!(client.api_key, "test-api-key");
+        assert_eq!(client.core.base_url.as_str(), "https://custom.example.com/");
+        assert_eq!(client.core.api_key.as_str(), "test-api-key");
         // Client always has a requester
     }
 
@@ -285,8 +1077,35 @@ mod tests {
             .build()
             .unwrap();
 
-        assert_eq!(client.base_url, DEFAULT_BASE_URL);
-        assert_eq!(client.api_key, "test-api-key");
+        assert_eq!(client.core.base_url.as_str(), "https://px6.link/");
+        assert_eq!(client.core.api_key.as_str(), "test-api-key");
+        assert_eq!(client.core.max_retries, 0);
+        assert_eq!(client.core.retry_backoff, DEFAULT_RETRY_BACKOFF);
+        assert!(client.core.rate_limiter.is_none());
+    }
+
+    #[test]
+    fn test_builder_build_with_rate_limit() {
+        let client = AsyncClientBuilder::new()
+            .api_key("test-api-key")
+            .rate_limit(3, Duration::from_secs(1))
+            .build()
+            .unwrap();
+
+        let cloned = client.clone();
+        assert!(Arc::ptr_eq(
+            client.core.rate_limiter.as_ref().unwrap(),
+            cloned.core.rate_limiter.as_ref().unwrap()
+        ));
+    }
+
+    #[test]
+    fn test_builder_build_with_zero_rate_limit_error() {
+        let result = AsyncClientBuilder::new()
+            .api_key("test-api-key")
+            .rate_limit(0, Duration::from_secs(1))
+            .build();
+        assert!(matches!(result, Err(ClientBuildError::RateLimitTooLow)));
     }
 
     #[test]
@@ -295,11 +1114,1397 @@ mod tests {
         assert!(matches!(result, Err(ClientBuildError::ApiKeyMustBeSet)));
     }
 
+    #[test]
+    fn test_builder_build_with_path_in_api_key_error() {
+        let result = AsyncClientBuilder::new().api_key("key/../getproxy").build();
+        assert!(matches!(
+            result,
+            Err(ClientBuildError::InvalidApiKey {
+                source: crate::BuildError::ApiKeyNotUrlSafe
+            })
+        ));
+    }
+
+    #[test]
+    fn test_client_debug_masks_api_key() {
+        let client = AsyncClient::builder()
+            .api_key("secret-api-key")
+            .build()
+            .unwrap();
+
+        let debug = format!("{client:?}");
+        assert!(!debug.contains("secret-api-key"));
+        assert!(debug.contains(r#"api_key: "se***""#));
+    }
+
     #[test]
     fn test_client_builder() {
         let client = AsyncClient::builder();
         assert_eq!(client.base_url, None);
         assert_eq!(client.api_key, None);
         assert!(client.requester.is_none());
+        assert!(client.transport.is_none());
+    }
+
+    const GET_COUNTRY_RESPONSE: &str =
+        r#"{"status":"yes","user_id":"1","balance":"48.80","currency":"RUB","list":["ru","us"]}"#;
+
+    #[tokio::test]
+    async fn test_retry_after_too_many_requests() {
+        let mut server = mockito::Server::new_async().await;
+        let throttled = server
+            .mock("GET", "/api/test-api-key/getcountry")
+            .match_query(mockito::Matcher::Any)
+            .with_status(429)
+            .expect(2)
+            .create_async()
+            .await;
+        let success = server
+            .mock("GET", "/api/test-api-key/getcountry")
+            .match_query(mockito::Matcher::Any)
+            .with_body(GET_COUNTRY_RESPONSE)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let client = AsyncClient::builder()
+            .base_url(server.url())
+            .api_key("test-api-key")
+            .max_retries(2)
+            .retry_backoff(Duration::from_millis(1))
+            .build()
+            .unwrap();
+
+        let response = client
+            .get_country(params::GetCountry { version: None })
+            .await
+            .unwrap();
+
+        assert_eq!(response.list.len(), 2);
+        throttled.assert_async().await;
+        success.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_retry_gives_up_after_max_retries() {
+        let mut server = mockito::Server::new_async().await;
+        let throttled = server
+            .mock("GET", "/api/test-api-key/getcountry")
+            .match_query(mockito::Matcher::Any)
+            .with_status(429)
+            .expect(2)
+            .create_async()
+            .await;
+
+        let client = AsyncClient::builder()
+            .base_url(server.url())
+            .api_key("test-api-key")
+            .max_retries(1)
+            .retry_backoff(Duration::from_millis(1))
+            .build()
+            .unwrap();
+
+        let result = client
+            .get_country(params::GetCountry { version: None })
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(error::ApiError::TooManyRequests { .. })
+        ));
+        throttled.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_buy_is_not_retried() {
+        let transport = transport::MockTransport::new()
+            .with_response(429, "")
+            .with_response(200, "");
+        let client = AsyncClient::builder()
+            .api_key("test-api-key")
+            .transport(transport.clone())
+            .max_retries(2)
+            .retry_backoff(Duration::from_millis(1))
+            .build()
+            .unwrap();
+
+        let result = client
+            .buy(params::Buy::new(
+                crate::ProxyCount::new(1).unwrap(),
+                crate::ProxyPeriod::new(7).unwrap(),
+                crate::Country::new("ru").unwrap(),
+            ))
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(error::ApiError::TooManyRequests { .. })
+        ));
+        assert_eq!(transport.requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_no_retry_by_default() {
+        let mut server = mockito::Server::new_async().await;
+        let throttled = server
+            .mock("GET", "/api/test-api-key/getcountry")
+            .match_query(mockito::Matcher::Any)
+            .with_status(429)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let client = AsyncClient::builder()
+            .base_url(server.url())
+            .api_key("test-api-key")
+            .build()
+            .unwrap();
+
+        let result = client
+            .get_country(params::GetCountry { version: None })
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(error::ApiError::TooManyRequests { .. })
+        ));
+        throttled.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_timeout_is_reqwest_error() {
+        // accepts connections but never responds
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+
+        let client = AsyncClient::builder()
+            .base_url(format!("http://{address}"))
+            .api_key("test-api-key")
+            .timeout(Duration::from_millis(100))
+            .build()
+            .unwrap();
+
+        let result = client
+            .get_country(params::GetCountry { version: None })
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(error::ApiError::ReqwestError { ref source }) if source.is_timeout()
+        ));
+        let message = format!("{:?}", result.unwrap_err());
+        assert!(!message.contains("test-api-key"));
+    }
+
+    #[tokio::test]
+    async fn test_default_headers_are_sent() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/api/test-api-key/getcountry")
+            .match_query(mockito::Matcher::Any)
+            .match_header("user-agent", "proxy6-test")
+            .with_body(GET_COUNTRY_RESPONSE)
+            .create_async()
+            .await;
+
+        let mut headers = reqwest::header::HeaderMap::new();
+        headers.insert(
+            reqwest::header::USER_AGENT,
+            reqwest::header::HeaderValue::from_static("proxy6-test"),
+        );
+        let client = AsyncClient::builder()
+            .base_url(server.url())
+            .api_key("test-api-key")
+            .default_headers(headers)
+            .build()
+            .unwrap();
+
+        client
+            .get_country(params::GetCountry { version: None })
+            .await
+            .unwrap();
+
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_html_body_is_non_json_response() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/api/test-api-key/getcountry")
+            .match_query(mockito::Matcher::Any)
+            .with_body("<html>Maintenance</html>")
+            .create_async()
+            .await;
+
+        let client = AsyncClient::builder()
+            .base_url(server.url())
+            .api_key("test-api-key")
+            .build()
+            .unwrap();
+
+        let result = client
+            .get_country(params::GetCountry { version: None })
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(error::ApiError::NonJsonResponse { .. })
+        ));
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_documented_error_has_message() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/api/test-api-key/getcountry")
+            .match_query(mockito::Matcher::Any)
+            .with_body(r#"{"status": "no", "error_id": 100, "error": "Error key"}"#)
+            .create_async()
+            .await;
+
+        let client = AsyncClient::builder()
+            .base_url(server.url())
+            .api_key("test-api-key")
+            .build()
+            .unwrap();
+
+        let result = client
+            .get_country(params::GetCountry { version: None })
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(error::ApiError::DocumentedError {
+                code: error::DocumentedErrorCode::Key,
+                message: Some(message),
+                ..
+            }) if message == "Error key"
+        ));
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_default_version_fills_unset_version() {
+        let mut server = mockito::Server::new_async().await;
+        let default_version = server
+            .mock("GET", "/api/test-api-key/getcountry")
+            .match_query(mockito::Matcher::UrlEncoded("version".into(), "4".into()))
+            .with_body(GET_COUNTRY_RESPONSE)
+            .expect(1)
+            .create_async()
+            .await;
+        let explicit_version = server
+            .mock("GET", "/api/test-api-key/getcountry")
+            .match_query(mockito::Matcher::UrlEncoded("version".into(), "6".into()))
+            .with_body(GET_COUNTRY_RESPONSE)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let client = AsyncClient::builder()
+            .base_url(server.url())
+            .api_key("test-api-key")
+            .default_version(crate::ProxyVersion::Ipv4)
+            .build()
+            .unwrap();
+
+        client
+            .get_country(params::GetCountry { version: None })
+            .await
+            .unwrap();
+        client
+            .get_country(params::GetCountry {
+                version: Some(crate::ProxyVersion::Ipv6),
+            })
+            .await
+            .unwrap();
+
+        default_version.assert_async().await;
+        explicit_version.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_buy_batched_keeps_completed_orders_on_error() {
+        let mut server = mockito::Server::new_async().await;
+        let first_order = server
+            .mock("GET", "/api/test-api-key/buy")
+            .match_query(mockito::Matcher::UrlEncoded("count".into(), "2".into()))
+            .with_body(r#"{"status":"yes","user_id":"1","balance":"42.5","currency":"RUB","order_id":1,"count":2,"price":10,"period":7,"country":"ru","list":[]}"#)
+            .expect(1)
+            .create_async()
+            .await;
+        let failed_order = server
+            .mock("GET", "/api/test-api-key/buy")
+            .match_query(mockito::Matcher::UrlEncoded("count".into(), "1".into()))
+            .with_body(r#"{"status":"no","error_id":400,"error":"Error no money"}"#)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let client = AsyncClient::builder()
+            .base_url(server.url())
+            .api_key("test-api-key")
+            .build()
+            .unwrap();
+
+        let result = client
+            .buy_batched(
+                params::Buy {
+                    count: crate::ProxyCount::new(3).unwrap(),
+                    period: crate::ProxyPeriod::new(7).unwrap(),
+                    country: crate::Country::new("ru").unwrap(),
+                    version: None,
+                    r#type: None,
+                    description: None,
+                    auto_prolong: false,
+                    keyed_list: false,
+                },
+                crate::ProxyCount::new(2).unwrap(),
+            )
+            .await;
+
+        let err = result.unwrap_err();
+        assert_eq!(err.completed.responses.len(), 1);
+        assert_eq!(err.completed.count(), 2);
+        assert!(matches!(
+            err.source,
+            error::ApiError::DocumentedError {
+                code: error::DocumentedErrorCode::NoMoney,
+                ..
+            }
+        ));
+        first_order.assert_async().await;
+        failed_order.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_too_long_url_is_not_sent() {
+        let client = AsyncClient::builder()
+            .base_url("http://127.0.0.1:1")
+            .api_key("test-api-key")
+            .build()
+            .unwrap();
+
+        let result = client
+            .set_type(params::SetType {
+                ids: crate::ProxyIds::new(
+                    (0..5000)
+                        .map(|id| crate::ProxyId::new(id.to_string()))
+                        .collect(),
+                )
+                .unwrap(),
+                r#type: crate::ProxyType::Http,
+            })
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(error::ApiError::RequestTooLong { length }) if length > 8000
+        ));
+    }
+
+    #[derive(Debug)]
+    struct StaticTransport(Result<transport::HttpResponse, &'static str>);
+
+    impl AsyncTransport for StaticTransport {
+        fn get<'a>(&'a self, _url: &'a str) -> transport::TransportFuture<'a> {
+            let response = self.0.clone().map_err(Into::into);
+            Box::pin(async move { response })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_custom_transport() {
+        let client = AsyncClient::builder()
+            .api_key("test-api-key")
+            .transport(StaticTransport(Ok(transport::HttpResponse {
+                status: 200,
+                body: GET_COUNTRY_RESPONSE.to_string(),
+            })))
+            .build()
+            .unwrap();
+
+        let response = client
+            .get_country(params::GetCountry { version: None })
+            .await
+            .unwrap();
+
+        assert_eq!(response.list.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_custom_transport_error() {
+        let client = AsyncClient::builder()
+            .api_key("test-api-key")
+            .transport(StaticTransport(Err("connection refused")))
+            .build()
+            .unwrap();
+
+        let result = client
+            .get_country(params::GetCountry { version: None })
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(error::ApiError::TransportError { source }) if source.to_string() == "connection refused"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_cache_countries_within_ttl() {
+        let transport = transport::MockTransport::new()
+            .with_response(200, GET_COUNTRY_RESPONSE)
+            .with_response(200, GET_COUNTRY_RESPONSE)
+            .with_response(200, GET_COUNTRY_RESPONSE);
+        let client = AsyncClient::builder()
+            .api_key("test-api-key")
+            .transport(transport.clone())
+            .cache_countries(Duration::from_mins(1))
+            .build()
+            .unwrap();
+        let cloned = client.clone();
+
+        let first = client
+            .get_country(params::GetCountry { version: None })
+            .await
+            .unwrap();
+        let second = cloned
+            .get_country(params::GetCountry { version: None })
+            .await
+            .unwrap();
+        assert_eq!(first.list, second.list);
+        assert_eq!(transport.requests().len(), 1);
+
+        client
+            .get_country(params::GetCountry {
+                version: Some(crate::ProxyVersion::Ipv6),
+            })
+            .await
+            .unwrap();
+        client.balance().await.unwrap();
+        assert_eq!(transport.requests().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_cache_countries_disabled_by_default() {
+        let transport = transport::MockTransport::new()
+            .with_response(200, GET_COUNTRY_RESPONSE)
+            .with_response(200, GET_COUNTRY_RESPONSE);
+        let client = AsyncClient::builder()
+            .api_key("test-api-key")
+            .transport(transport.clone())
+            .build()
+            .unwrap();
+
+        for _ in 0..2 {
+            client
+                .get_country(params::GetCountry { version: None })
+                .await
+                .unwrap();
+        }
+        assert_eq!(transport.requests().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_ping() {
+        let transport = transport::MockTransport::new()
+            .with_response(200, GET_COUNTRY_RESPONSE)
+            .with_response(200, r#"{"status":"no","error_id":100,"error":"Error key"}"#);
+        let client = AsyncClient::builder()
+            .api_key("test-api-key")
+            .transport(transport.clone())
+            .build()
+            .unwrap();
+
+        assert_eq!(client.ping().await.unwrap(), response::Ping::Authorized);
+        assert_eq!(client.ping().await.unwrap(), response::Ping::BadKey);
+        assert_eq!(
+            transport.requests()[0],
+            "https://px6.link/api/test-api-key/getcountry?format=json"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_ping_network_error() {
+        let client = AsyncClient::builder()
+            .api_key("test-api-key")
+            .transport(StaticTransport(Err("connection refused")))
+            .build()
+            .unwrap();
+
+        assert!(matches!(
+            client.ping().await,
+            Err(error::ApiError::TransportError { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_buy_response_with_mock_transport() {
+        let transport = transport::MockTransport::new().with_response(200, r#"{"status":"yes","user_id":"1","balance":42.5,"currency":"RUB","order_id":12345,"count":1,"price":6.3,"period":7,"country":"ru","list":[{"id":"15","ip":"2a00:1838:32:19f:45fb:2640::330","host":"185.22.134.250","port":"7330","user":"5svBNZ","pass":"iagn2d","type":"http","date":"2016-06-19 16:32:39","date_end":"2016-07-12 11:50:41","unixtime":1466379159,"unixtime_end":1468349441,"active":"1"}]}"#);
+        let client = AsyncClient::builder()
+            .api_key("test-api-key")
+            .transport(transport.clone())
+            .build()
+            .unwrap();
+
+        let response = client
+            .buy(params::Buy::new(
+                crate::ProxyCount::new(1).unwrap(),
+                crate::ProxyPeriod::new(7).unwrap(),
+                crate::Country::new("ru").unwrap(),
+            ))
+            .await
+            .unwrap();
+
+        assert_eq!(response.order_id.as_usize(), 12345);
+        assert!((response.price.as_f64() - 6.3).abs() < f64::EPSILON);
+        assert_eq!(response.list[0].id.as_str(), "15");
+        assert_eq!(response.list[0].port.as_u16(), 7330);
+        assert_eq!(
+            transport.requests(),
+            ["https://px6.link/api/test-api-key/buy?count=1&period=7&country=ru&nokey&format=json"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_ipv4_shared_requests_with_mock_transport() {
+        let transport = transport::MockTransport::new()
+            .with_response(200, r#"{"status":"yes","user_id":"1","balance":"48.80","currency":"RUB","price":45,"price_single":0.45,"period":30,"count":100}"#)
+            .with_response(200, r#"{"status":"yes","user_id":"1","balance":"48.80","currency":"RUB","count":412}"#)
+            .with_response(200, r#"{"status":"yes","user_id":"1","balance":42.5,"currency":"RUB","order_id":12345,"count":1,"price":0.45,"period":30,"country":"ru","list":[{"id":"15","ip":"185.22.134.250","host":"185.22.134.250","port":"7331","user":"5svBNZ","pass":"iagn2d","type":"http","date":"2016-06-19 16:32:39","date_end":"2016-07-12 11:50:41","unixtime":1466379159,"unixtime_end":1468349441,"active":"1"}]}"#);
+        let client = AsyncClient::builder()
+            .api_key("test-api-key")
+            .transport(transport.clone())
+            .build()
+            .unwrap();
+
+        let price = client
+            .get_price(params::GetPrice {
+                count: crate::ProxyCount::new(100).unwrap(),
+                period: crate::ProxyPeriod::new(30).unwrap(),
+                version: Some(ProxyVersion::Ipv4Shared),
+            })
+            .await
+            .unwrap();
+        let count = client
+            .get_count(params::GetCount {
+                country: crate::Country::new("ru").unwrap(),
+                version: Some(ProxyVersion::Ipv4Shared),
+            })
+            .await
+            .unwrap();
+        let bought = client
+            .buy(
+                params::Buy::new(
+                    crate::ProxyCount::new(1).unwrap(),
+                    crate::ProxyPeriod::new(30).unwrap(),
+                    crate::Country::new("ru").unwrap(),
+                )
+                .version(ProxyVersion::Ipv4Shared),
+            )
+            .await
+            .unwrap();
+
+        assert!((price.price.as_f64() - 45.0).abs() < f64::EPSILON);
+        assert_eq!(count.count, 412);
+        assert_eq!(bought.list[0].ip, bought.list[0].host);
+        assert_eq!(bought.list[0].port.as_u16(), 7331);
+        assert_eq!(
+            transport.requests(),
+            [
+                "https://px6.link/api/test-api-key/getprice?count=100&period=30&version=3&format=json",
+                "https://px6.link/api/test-api-key/getcount?country=ru&version=3&format=json",
+                "https://px6.link/api/test-api-key/buy?count=1&period=30&country=ru&version=3&nokey&format=json",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_price_response_with_mock_transport() {
+        let transport = transport::MockTransport::new().with_response(200, r#"{"status":"yes","user_id":"1","balance":"48.80","currency":"RUB","price":1800,"price_single":0.6,"period":30,"count":100}"#);
+        let client = AsyncClient::builder()
+            .api_key("test-api-key")
+            .transport(transport)
+            .build()
+            .unwrap();
+
+        let response = client
+            .get_price(params::GetPrice {
+                count: crate::ProxyCount::new(100).unwrap(),
+                period: crate::ProxyPeriod::new(30).unwrap(),
+                version: None,
+            })
+            .await
+            .unwrap();
+
+        assert!((response.price.as_f64() - 1800.0).abs() < f64::EPSILON);
+        assert!((response.price_single.as_f64() - 0.6).abs() < f64::EPSILON);
+        assert_eq!(response.count, 100);
+    }
+
+    #[tokio::test]
+    async fn test_unknown_error_keeps_status() {
+        let transport =
+            transport::MockTransport::new().with_response(503, "Service Temporarily Unavailable");
+        let client = AsyncClient::builder()
+            .api_key("test-api-key")
+            .transport(transport)
+            .build()
+            .unwrap();
+
+        let result = client
+            .get_country(params::GetCountry { version: None })
+            .await;
+
+        match result {
+            Err(error::ApiError::UnknownError { status, response }) => {
+                assert_eq!(status, 503);
+                assert_eq!(response, "Service Temporarily Unavailable");
+            }
+            other => panic!("Expected UnknownError, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_documented_error_with_mock_transport() {
+        let client = AsyncClient::builder()
+            .api_key("test-api-key")
+            .transport(
+                transport::MockTransport::new()
+                    .with_response(200, r#"{"status":"no","error_id":100,"error":"Error key"}"#),
+            )
+            .build()
+            .unwrap();
+
+        let result = client
+            .get_country(params::GetCountry { version: None })
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(error::ApiError::DocumentedError {
+                code: error::DocumentedErrorCode::Key,
+                ..
+            })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_balance() {
+        let transport = transport::MockTransport::new().with_response(200, GET_COUNTRY_RESPONSE);
+        let client = AsyncClient::builder()
+            .api_key("test-api-key")
+            .transport(transport.clone())
+            .build()
+            .unwrap();
+
+        let response = client.balance().await.unwrap();
+
+        assert_eq!(response.user_id.as_str(), "1");
+        assert_eq!(response.balance.as_str(), "48.80");
+        assert_eq!(response.currency.as_str(), "RUB");
+        assert_eq!(
+            transport.requests(),
+            ["https://px6.link/api/test-api-key/getcountry?format=json"]
+        );
+    }
+
+    #[test]
+    fn test_builder_build_with_zero_max_concurrent_error() {
+        let result = AsyncClientBuilder::new()
+            .api_key("test-api-key")
+            .max_concurrent(0)
+            .build();
+
+        assert!(matches!(
+            result,
+            Err(ClientBuildError::ConcurrencyLimitTooLow)
+        ));
+    }
+
+    #[derive(Debug, Default)]
+    struct InFlightTransport {
+        in_flight: std::sync::atomic::AtomicUsize,
+        max_in_flight: std::sync::atomic::AtomicUsize,
+    }
+
+    impl transport::AsyncTransport for Arc<InFlightTransport> {
+        fn get<'a>(&'a self, _url: &'a str) -> transport::TransportFuture<'a> {
+            use std::sync::atomic::Ordering;
+
+            Box::pin(async move {
+                let in_flight = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                self.max_in_flight.fetch_max(in_flight, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_millis(20)).await;
+                self.in_flight.fetch_sub(1, Ordering::SeqCst);
+
+                Ok(transport::HttpResponse {
+                    status: 200,
+                    body: GET_COUNTRY_RESPONSE.to_string(),
+                })
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_max_concurrent_bounds_in_flight_requests() {
+        let transport = Arc::new(InFlightTransport::default());
+        let client = AsyncClient::builder()
+            .api_key("test-api-key")
+            .transport(Arc::clone(&transport))
+            .max_concurrent(2)
+            .build()
+            .unwrap();
+
+        let tasks = (0..6)
+            .map(|_| {
+                let client = client.clone();
+                tokio::spawn(async move { client.balance().await })
+            })
+            .collect::<Vec<_>>();
+        for task in tasks {
+            task.await.unwrap().unwrap();
+        }
+
+        assert_eq!(
+            transport
+                .max_in_flight
+                .load(std::sync::atomic::Ordering::SeqCst),
+            2
+        );
+    }
+
+    #[tokio::test]
+    async fn test_ip_auth_echoes_requested_ips() {
+        let client = AsyncClient::builder()
+            .api_key("test-api-key")
+            .transport(transport::MockTransport::new().with_response(
+                200,
+                r#"{"status":"yes","user_id":"1","balance":"48.80","currency":"RUB"}"#,
+            ))
+            .build()
+            .unwrap();
+        let ip = crate::IpsToConnect::connect(vec!["127.0.0.1".parse().unwrap()]).unwrap();
+
+        let response = client
+            .ip_auth(params::IpAuth { ip: ip.clone() })
+            .await
+            .unwrap();
+
+        assert_eq!(response.ip, Some(ip));
+    }
+
+    #[tokio::test]
+    async fn test_on_request_hook() {
+        let calls = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let recorded = Arc::clone(&calls);
+        let client = AsyncClient::builder()
+            .api_key("test-api-key")
+            .transport(
+                transport::MockTransport::new()
+                    .with_response(200, GET_COUNTRY_RESPONSE)
+                    .with_response(500, "Internal Server Error"),
+            )
+            .on_request(move |method, _elapsed, result| {
+                recorded.lock().unwrap().push((method, result.is_ok()));
+            })
+            .build()
+            .unwrap();
+
+        client.balance().await.unwrap();
+        client.balance().await.unwrap_err();
+
+        assert_eq!(
+            *calls.lock().unwrap(),
+            [
+                (MethodName::GetCountry, true),
+                (MethodName::GetCountry, false)
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_call_raw() {
+        let transport = transport::MockTransport::new()
+            .with_response(
+                200,
+                r#"{"status":"yes","user_id":"1","balance":"48.80","currency":"RUB","thing":42}"#,
+            )
+            .with_response(
+                200,
+                r#"{"status":"no","error_id":110,"error":"Error method"}"#,
+            );
+        let client = AsyncClient::builder()
+            .api_key("test-api-key")
+            .transport(transport.clone())
+            .build()
+            .unwrap();
+
+        let response = client
+            .call_raw("newthing", &[("ids", "1,2"), ("nokey", "")])
+            .await
+            .unwrap();
+        let result = client.call_raw("newthing", &[]).await;
+
+        assert_eq!(response["thing"], 42);
+        assert!(matches!(
+            result,
+            Err(error::ApiError::DocumentedError {
+                code: error::DocumentedErrorCode::Method,
+                ..
+            })
+        ));
+        assert_eq!(
+            transport.requests()[0],
+            "https://px6.link/api/test-api-key/newthing?ids=1,2&nokey&format=json"
+        );
+    }
+
+    #[test]
+    fn test_build_url() {
+        let client = AsyncClient::builder()
+            .api_key("test-api-key")
+            .base_url("http://localhost:1234")
+            .default_version(crate::ProxyVersion::Ipv6)
+            .build()
+            .unwrap();
+
+        let url = client.build_url(&ApiMethod::GetPrice(params::GetPrice {
+            count: crate::ProxyCount::new(10).unwrap(),
+            period: crate::ProxyPeriod::new(30).unwrap(),
+            version: None,
+        }));
+
+        assert_eq!(
+            url,
+            "http://localhost:1234/api/test-api-key/getprice?count=10&period=30&version=6&format=json"
+        );
+    }
+
+    #[test]
+    fn test_build_url_base_url_trailing_slash() {
+        let method = ApiMethod::GetCountry(params::GetCountry { version: None });
+        let urls = [
+            "https://px6.link",
+            "https://px6.link/",
+            "https://px6.link//",
+        ]
+        .map(|base_url| {
+            AsyncClient::builder()
+                .api_key("test-api-key")
+                .base_url(base_url)
+                .build()
+                .unwrap()
+                .build_url(&method)
+        });
+
+        assert_eq!(
+            urls,
+            ["https://px6.link/api/test-api-key/getcountry?format=json"; 3]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_via_proxy_routes_requests_through_proxy() {
+        let mut proxy = mockito::Server::new_async().await;
+        let mock = proxy
+            .mock("GET", "/api/test-api-key/getcountry")
+            .match_query(mockito::Matcher::Any)
+            .with_body(GET_COUNTRY_RESPONSE)
+            .create_async()
+            .await;
+        let client = AsyncClient::builder()
+            .api_key("test-api-key")
+            .base_url("http://px6.invalid")
+            .via_proxy(reqwest::Proxy::http(proxy.url()).unwrap())
+            .build()
+            .unwrap();
+
+        client.balance().await.unwrap();
+
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_get_proxy_multi_deduplicates_proxies() {
+        let transport = transport::MockTransport::new()
+            .with_response(200, get_proxy_page_response(&["1", "2"], 2))
+            .with_response(200, get_proxy_page_response(&["2", "3"], 2));
+        let client = AsyncClient::builder()
+            .api_key("test-api-key")
+            .transport(transport.clone())
+            .build()
+            .unwrap();
+
+        let proxies = client
+            .get_proxy_multi(
+                &[crate::ProxyStatus::Active, crate::ProxyStatus::Expiring],
+                &params::GetProxy::builder().build(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(
+            proxies
+                .iter()
+                .map(|proxy| proxy.id.as_str())
+                .collect::<Vec<_>>(),
+            ["1", "2", "3"]
+        );
+        let requests = transport.requests();
+        assert!(requests[0].contains("state=active"));
+        assert!(requests[1].contains("state=expiring"));
+    }
+
+    #[tokio::test]
+    async fn test_get_proxy_lenient_keeps_valid_proxies() {
+        let body = get_proxy_page_response(&["1", "2"], 2).replacen(
+            r#""port":"7330""#,
+            r#""port":"x""#,
+            1,
+        );
+        let transport = transport::MockTransport::new().with_response(200, &body);
+        let client = AsyncClient::builder()
+            .api_key("test-api-key")
+            .transport(transport)
+            .build()
+            .unwrap();
+
+        let response = client
+            .get_proxy_lenient(params::GetProxy::builder().build())
+            .await
+            .unwrap();
+
+        assert_eq!(
+            response
+                .proxies()
+                .map(|proxy| proxy.id.as_str())
+                .collect::<Vec<_>>(),
+            ["2"]
+        );
+        assert_eq!(response.errors().count(), 1);
+    }
+
+    fn expiring_proxies_response(expires_in: &[(&str, u64)]) -> String {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        let mut body: serde_json::Value = serde_json::from_str(&get_proxy_page_response(
+            &expires_in.iter().map(|(id, _)| *id).collect::<Vec<_>>(),
+            expires_in.len(),
+        ))
+        .unwrap();
+        for (proxy, (_, seconds)) in body["list"]
+            .as_array_mut()
+            .unwrap()
+            .iter_mut()
+            .zip(expires_in)
+        {
+            proxy["unixtime_end"] = (now + seconds).into();
+        }
+
+        body.to_string()
+    }
+
+    #[tokio::test]
+    async fn test_prolong_expiring_prolongs_selected_proxies() {
+        let transport = transport::MockTransport::new()
+            .with_response(
+                200,
+                expiring_proxies_response(&[("1", 3600), ("2", 10 * 86400), ("3", 7200)]),
+            )
+            .with_response(200, r#"{"status":"yes","user_id":"1","balance":"48.80","currency":"RUB","order_id":1,"price":2.5,"period":7,"count":2,"list":{"1":{"id":"1","date_end":"2016-07-12 11:50:41","unixtime_end":1468349441},"3":{"id":"3","date_end":"2016-07-12 11:50:41","unixtime_end":1468349441}}}"#);
+        let client = AsyncClient::builder()
+            .api_key("test-api-key")
+            .transport(transport.clone())
+            .build()
+            .unwrap();
+
+        let response = client
+            .prolong_expiring(
+                Duration::from_hours(24),
+                crate::ProxyPeriod::new(7).unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.count(), 2);
+        let requests = transport.requests();
+        assert_eq!(requests.len(), 2);
+        assert!(requests[1].contains("/prolong?period=7&ids=1,3&"));
+    }
+
+    #[tokio::test]
+    async fn test_prolong_expiring_without_expiring_proxies_sends_nothing() {
+        let transport = transport::MockTransport::new()
+            .with_response(200, expiring_proxies_response(&[("1", 10 * 86400)]));
+        let client = AsyncClient::builder()
+            .api_key("test-api-key")
+            .transport(transport.clone())
+            .build()
+            .unwrap();
+
+        let response = client
+            .prolong_expiring(
+                Duration::from_hours(24),
+                crate::ProxyPeriod::new(7).unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert!(response.responses.is_empty());
+        assert_eq!(transport.requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_prolong_expiring_splits_many_ids() {
+        let ids: Vec<String> = (1..=201).map(|id| id.to_string()).collect();
+        let expires_in: Vec<_> = ids.iter().map(|id| (id.as_str(), 3600)).collect();
+        let prolonged = r#"{"status":"yes","user_id":"1","balance":"48.80","currency":"RUB","order_id":1,"price":2.5,"period":7,"count":1,"list":[]}"#;
+        let transport = transport::MockTransport::new()
+            .with_response(200, expiring_proxies_response(&expires_in))
+            .with_response(200, prolonged)
+            .with_response(200, prolonged);
+        let client = AsyncClient::builder()
+            .api_key("test-api-key")
+            .transport(transport.clone())
+            .build()
+            .unwrap();
+
+        let response = client
+            .prolong_expiring(
+                Duration::from_hours(24),
+                crate::ProxyPeriod::new(7).unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.responses.len(), 2);
+        let requests = transport.requests();
+        assert_eq!(requests.len(), 3);
+        assert!(requests[2].contains("/prolong?period=7&ids=201&"));
+    }
+
+    #[tokio::test]
+    async fn test_country_availability() {
+        let transport = transport::MockTransport::new()
+            .with_response(200, r#"{"status":"yes","user_id":"1","balance":"48.80","currency":"RUB","list":["ru","us"]}"#)
+            .with_response(200, r#"{"status":"yes","user_id":"1","balance":"48.80","currency":"RUB","count":10}"#)
+            .with_response(200, r#"{"status":"yes","user_id":"1","balance":"48.80","currency":"RUB","count":0}"#);
+        let client = AsyncClient::builder()
+            .api_key("test-api-key")
+            .transport(transport.clone())
+            .rate_limit(10, Duration::from_secs(1))
+            .build()
+            .unwrap();
+
+        let availability = client
+            .country_availability(Some(crate::ProxyVersion::Ipv6))
+            .await
+            .unwrap();
+
+        assert_eq!(availability.len(), 2);
+        assert_eq!(availability[&crate::Country::new("ru").unwrap()], 10);
+        assert_eq!(availability[&crate::Country::new("us").unwrap()], 0);
+        assert_eq!(
+            transport.requests(),
+            [
+                "https://px6.link/api/test-api-key/getcountry?version=6&format=json",
+                "https://px6.link/api/test-api-key/getcount?country=ru&version=6&format=json",
+                "https://px6.link/api/test-api-key/getcount?country=us&version=6&format=json",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_country_availability_spaces_requests_without_rate_limit() {
+        let transport = transport::MockTransport::new()
+            .with_response(200, r#"{"status":"yes","user_id":"1","balance":"48.80","currency":"RUB","list":["ru"]}"#)
+            .with_response(200, r#"{"status":"yes","user_id":"1","balance":"48.80","currency":"RUB","count":10}"#);
+        let client = AsyncClient::builder()
+            .api_key("test-api-key")
+            .transport(transport)
+            .build()
+            .unwrap();
+
+        let started = Instant::now();
+        client.country_availability(None).await.unwrap();
+
+        assert!(started.elapsed() >= API_REQUEST_SPACING);
+    }
+
+    #[tokio::test]
+    async fn test_delete_idempotent_treats_not_found_as_success() {
+        let transport = transport::MockTransport::new()
+            .with_response(
+                200,
+                r#"{"status":"yes","user_id":"1","balance":"48.80","currency":"RUB","count":2}"#,
+            )
+            .with_response(
+                200,
+                r#"{"status":"no","error_id":404,"error":"Element not found"}"#,
+            )
+            .with_response(200, r#"{"status":"no","error_id":100,"error":"Error key"}"#);
+        let client = AsyncClient::builder()
+            .api_key("test-api-key")
+            .transport(transport)
+            .build()
+            .unwrap();
+        let params = params::Delete {
+            target: crate::DeleteTarget::new(
+                Some(vec![crate::ProxyId::new("1"), crate::ProxyId::new("2")]),
+                None,
+            )
+            .unwrap(),
+        };
+
+        assert_eq!(client.delete_idempotent(params.clone()).await.unwrap(), 2);
+        assert_eq!(client.delete_idempotent(params.clone()).await.unwrap(), 0);
+        assert!(matches!(
+            client.delete_idempotent(params).await,
+            Err(error::ApiError::DocumentedError {
+                code: error::DocumentedErrorCode::Key,
+                ..
+            })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_set_type_idempotent_treats_already_set_type_as_success() {
+        let transport = transport::MockTransport::new()
+            .with_response(
+                200,
+                r#"{"status":"yes","user_id":"1","balance":"48.80","currency":"RUB"}"#,
+            )
+            .with_response(
+                200,
+                r#"{"status":"no","error_id":30,"error":"Error unknown"}"#,
+            )
+            .with_response(
+                200,
+                r#"{"status":"no","error_id":404,"error":"Element not found"}"#,
+            );
+        let client = AsyncClient::builder()
+            .api_key("test-api-key")
+            .transport(transport)
+            .build()
+            .unwrap();
+        let params = params::SetType {
+            ids: crate::ProxyIds::new(vec![crate::ProxyId::new("1")]).unwrap(),
+            r#type: crate::ProxyType::Socks5,
+        };
+
+        assert!(
+            client
+                .set_type_idempotent(params.clone())
+                .await
+                .unwrap()
+                .is_some()
+        );
+        assert!(
+            client
+                .set_type_idempotent(params.clone())
+                .await
+                .unwrap()
+                .is_none()
+        );
+        assert!(matches!(
+            client.set_type_idempotent(params).await,
+            Err(error::ApiError::DocumentedError {
+                code: error::DocumentedErrorCode::NotFound,
+                ..
+            })
+        ));
+    }
+
+    fn get_proxy_page_response(ids: &[&str], list_count: usize) -> String {
+        let list = ids
+            .iter()
+            .map(|id| {
+                format!(
+                    r#"{{"id":"{id}","ip":"185.22.134.250","host":"185.22.134.250","port":"7330","user":"user","pass":"pass","type":"http","country":"ru","date":"2016-06-19 16:32:39","date_end":"2016-07-12 11:50:41","unixtime":1466379159,"unixtime_end":1468349441,"descr":"","active":"1"}}"#
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(",");
+
+        format!(
+            r#"{{"status":"yes","user_id":"1","balance":"48.80","currency":"RUB","list_count":{list_count},"list":[{list}]}}"#
+        )
+    }
+
+    #[tokio::test]
+    async fn test_get_proxy_filtered_keeps_matching_proxies() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/api/test-api-key/getproxy")
+            .match_query(mockito::Matcher::Any)
+            .with_body(get_proxy_page_response(&["1", "2", "3"], 3))
+            .create_async()
+            .await;
+
+        let client = AsyncClient::builder()
+            .base_url(server.url())
+            .api_key("test-api-key")
+            .build()
+            .unwrap();
+
+        let response = client
+            .get_proxy_filtered(
+                params::GetProxy {
+                    state: None,
+                    description: None,
+                    page: None,
+                    limit: None,
+                    keyed_list: false,
+                },
+                |proxy| proxy.id.as_str() != "2",
+            )
+            .await
+            .unwrap();
+
+        let ids = response
+            .list
+            .iter()
+            .map(|proxy| proxy.id.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(ids, ["1", "3"]);
+        assert_eq!(response.list_count, 3);
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_get_all_proxies_collects_every_page() {
+        let mut server = mockito::Server::new_async().await;
+        let first_page = server
+            .mock("GET", "/api/test-api-key/getproxy")
+            .match_query(mockito::Matcher::AllOf(vec![
+                mockito::Matcher::UrlEncoded("page".into(), "1".into()),
+                mockito::Matcher::UrlEncoded("limit".into(), "2".into()),
+            ]))
+            .with_body(get_proxy_page_response(&["1", "2"], 3))
+            .expect(1)
+            .create_async()
+            .await;
+        let second_page = server
+            .mock("GET", "/api/test-api-key/getproxy")
+            .match_query(mockito::Matcher::AllOf(vec![
+                mockito::Matcher::UrlEncoded("page".into(), "2".into()),
+                mockito::Matcher::UrlEncoded("limit".into(), "2".into()),
+            ]))
+            .with_body(get_proxy_page_response(&["3"], 3))
+            .expect(1)
+            .create_async()
+            .await;
+
+        let client = AsyncClient::builder()
+            .base_url(server.url())
+            .api_key("test-api-key")
+            .build()
+            .unwrap();
+
+        let proxies = client
+            .get_all_proxies(params::GetProxy {
+                state: None,
+                description: None,
+                page: None,
+                limit: Some(crate::PageLimit::new(2).unwrap()),
+                keyed_list: false,
+            })
+            .await
+            .unwrap();
+
+        let ids = proxies
+            .iter()
+            .map(|proxy| proxy.id.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(ids, ["1", "2", "3"]);
+        first_page.assert_async().await;
+        second_page.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_raw_method_returns_body() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/api/test-api-key/getcountry")
+            .match_query(mockito::Matcher::Any)
+            .with_body(GET_COUNTRY_RESPONSE)
+            .create_async()
+            .await;
+
+        let client = AsyncClient::builder()
+            .base_url(server.url())
+            .api_key("test-api-key")
+            .build()
+            .unwrap();
+
+        let raw = client
+            .get_country_raw(params::GetCountry { version: None })
+            .await
+            .unwrap();
+
+        assert_eq!(raw.body, GET_COUNTRY_RESPONSE);
+        assert_eq!(raw.data.list.len(), 2);
+    }
+
+    #[cfg(feature = "tracing")]
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_tracing_logs_method_without_api_key() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/api/test-api-key/getcountry")
+            .match_query(mockito::Matcher::Any)
+            .with_status(429)
+            .create_async()
+            .await;
+
+        let client = AsyncClient::builder()
+            .base_url(server.url())
+            .api_key("test-api-key")
+            .build()
+            .unwrap();
+
+        let _ = client
+            .get_country(params::GetCountry { version: None })
+            .await;
+
+        assert!(logs_contain("proxy6_request{method=getcountry}"));
+        assert!(logs_contain("sending request"));
+        assert!(logs_contain("request failed"));
+        assert!(!logs_contain("test-api-key"));
+    }
+
+    #[cfg(feature = "tracing")]
+    #[tokio::test]
+    #[tracing_test::traced_test]
+    async fn test_tracing_logs_check_without_proxy_password() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/api/test-api-key/check")
+            .match_query(mockito::Matcher::Any)
+            .with_status(500)
+            .create_async()
+            .await;
+
+        let client = AsyncClient::builder()
+            .base_url(server.url())
+            .api_key("test-api-key")
+            .build()
+            .unwrap();
+
+        let _ = client
+            .check(params::Check::by_proxy_string(
+                crate::ProxyString::new("127.0.0.1:8080:user:secret").unwrap(),
+            ))
+            .await;
+
+        assert!(logs_contain("proxy=127.0.0.1:8080:user:***"));
+        assert!(logs_contain("request failed"));
+        assert!(!logs_contain("secret"));
     }
 }
@@ -0,0 +1,100 @@
+use std::{
+    collections::HashMap,
+    sync::{Mutex, PoisonError},
+    time::Duration,
+};
+
+use crate::{ProxyVersion, response::GetCountry, time::Instant};
+
+/// Memoizes `getcountry` responses per proxy version for `ttl`.
+///
+/// Only the latest response of every version is kept, expired ones are replaced on the next insert.
+#[derive(Debug)]
+pub struct CountryCache {
+    ttl: Duration,
+    entries: Mutex<HashMap<Option<ProxyVersion>, (Instant, GetCountry)>>,
+}
+
+impl CountryCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Returns the cached response of `version` if it is younger than the TTL.
+    pub fn get(&self, version: Option<&ProxyVersion>) -> Option<GetCountry> {
+        self.get_at(version, Instant::now())
+    }
+
+    pub fn insert(&self, version: Option<ProxyVersion>, response: GetCountry) {
+        self.insert_at(version, response, Instant::now());
+    }
+
+    fn get_at(&self, version: Option<&ProxyVersion>, now: Instant) -> Option<GetCountry> {
+        let entries = self.entries.lock().unwrap_or_else(PoisonError::into_inner);
+
+        entries
+            .get(&version.cloned())
+            .filter(|(cached_at, _)| now.saturating_duration_since(*cached_at) < self.ttl)
+            .map(|(_, response)| response.clone())
+    }
+
+    fn insert_at(&self, version: Option<ProxyVersion>, response: GetCountry, now: Instant) {
+        self.entries
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(version, (now, response));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn get_country(countries: &str) -> GetCountry {
+        serde_json::from_str(&format!(
+            r#"{{"status":"yes","user_id":"1","balance":"48.80","currency":"RUB","list":[{countries}]}}"#
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_get_within_ttl() {
+        let cache = CountryCache::new(Duration::from_mins(1));
+        let now = Instant::now();
+        cache.insert_at(None, get_country(r#""ru""#), now);
+
+        let cached = cache.get_at(None, now + Duration::from_secs(59)).unwrap();
+        assert_eq!(cached.list, get_country(r#""ru""#).list);
+    }
+
+    #[test]
+    fn test_get_after_ttl_is_none() {
+        let cache = CountryCache::new(Duration::from_mins(1));
+        let now = Instant::now();
+        cache.insert_at(None, get_country(r#""ru""#), now);
+
+        assert!(cache.get_at(None, now + Duration::from_mins(1)).is_none());
+    }
+
+    #[test]
+    fn test_get_is_per_version() {
+        let cache = CountryCache::new(Duration::from_mins(1));
+        let now = Instant::now();
+        cache.insert_at(Some(ProxyVersion::Ipv6), get_country(r#""us""#), now);
+
+        assert!(cache.get_at(None, now).is_none());
+        assert_eq!(
+            cache
+                .get_at(Some(&ProxyVersion::Ipv6), now)
+                .unwrap()
+                .list
+                .len(),
+            1
+        );
+    }
+}
@@ -0,0 +1,345 @@
+use std::{borrow::Cow, sync::Arc, time::Duration};
+
+use crate::{
+    ApiKey, ApiResult, ClientBuildError, ProxyVersion, RequestHook, build_request_url,
+    cache::CountryCache,
+    error::ApiError,
+    method::ApiMethod,
+    params, parse_base_url,
+    rate_limit::RateLimiter,
+    response,
+    time::Instant,
+    transport::{self, TransportError},
+};
+
+pub const DEFAULT_RETRY_BACKOFF: Duration = Duration::from_secs(1);
+pub const DEFAULT_MAX_URL_LENGTH: usize = 8000;
+
+/// Builder settings shared by both clients, validated by [`ClientCore::new`].
+#[derive(Debug, Clone, Default)]
+pub struct CoreOptions {
+    pub base_url: Option<String>,
+    pub api_key: Option<ApiKey>,
+    pub max_retries: Option<u32>,
+    pub retry_backoff: Option<Duration>,
+    pub rate_limit: Option<(u32, Duration)>,
+    pub default_version: Option<ProxyVersion>,
+    pub cache_countries: Option<Duration>,
+    pub max_url_length: Option<usize>,
+    pub on_request: Option<RequestHook>,
+}
+
+/// State and request logic shared by [`crate::AsyncClient`] and [`crate::SyncClient`], generic over the transport.
+///
+/// Clients only add what differs between blocking and async code: sending, waiting and the public methods.
+pub struct ClientCore<T: ?Sized> {
+    pub base_url: url::Url,
+    pub transport: Arc<T>,
+    pub api_key: ApiKey,
+    pub max_retries: u32,
+    pub retry_backoff: Duration,
+    pub rate_limiter: Option<Arc<RateLimiter>>,
+    pub default_version: Option<ProxyVersion>,
+    pub country_cache: Option<Arc<CountryCache>>,
+    pub max_url_length: usize,
+    pub on_request: Option<RequestHook>,
+}
+
+impl<T: ?Sized> Clone for ClientCore<T> {
+    fn clone(&self) -> Self {
+        Self {
+            base_url: self.base_url.clone(),
+            transport: Arc::clone(&self.transport),
+            api_key: self.api_key.clone(),
+            max_retries: self.max_retries,
+            retry_backoff: self.retry_backoff,
+            rate_limiter: self.rate_limiter.clone(),
+            default_version: self.default_version.clone(),
+            country_cache: self.country_cache.clone(),
+            max_url_length: self.max_url_length,
+            on_request: self.on_request.clone(),
+        }
+    }
+}
+
+impl<T: ?Sized> ClientCore<T> {
+    /// # Errors
+    /// - [`ClientBuildError::InvalidBaseUrl`] if the base URL is not a valid http(s) URL.
+    /// - [`ClientBuildError::ApiKeyMustBeSet`] if the API key is not set.
+    /// - [`ClientBuildError::InvalidApiKey`] if the API key is empty or not URL-safe.
+    /// - [`ClientBuildError::RateLimitTooLow`] if the rate limit allows zero requests.
+    pub fn new(transport: Arc<T>, options: CoreOptions) -> Result<Self, ClientBuildError> {
+        let base_url = parse_base_url(options.base_url)?;
+        let api_key = options
+            .api_key
+            .ok_or(ClientBuildError::ApiKeyMustBeSet)?
+            .validated()
+            .map_err(|source| ClientBuildError::InvalidApiKey { source })?;
+        let rate_limiter = match options.rate_limit {
+            Some((0, _)) => return Err(ClientBuildError::RateLimitTooLow),
+            Some((requests, per)) => Some(Arc::new(RateLimiter::new(requests, per))),
+            None => None,
+        };
+
+        Ok(Self {
+            base_url,
+            transport,
+            api_key,
+            max_retries: options.max_retries.unwrap_or_default(),
+            retry_backoff: options.retry_backoff.unwrap_or(DEFAULT_RETRY_BACKOFF),
+            rate_limiter,
+            default_version: options.default_version,
+            country_cache: options
+                .cache_countries
+                .map(|ttl| Arc::new(CountryCache::new(ttl))),
+            max_url_length: options.max_url_length.unwrap_or(DEFAULT_MAX_URL_LENGTH),
+            on_request: options.on_request,
+        })
+    }
+
+    /// Full URL of the method with client defaults applied.
+    pub fn build_url(&self, method: &ApiMethod) -> String {
+        build_request_url(
+            &self.base_url,
+            self.api_key.as_str(),
+            &self.with_defaults(method),
+        )
+        .into()
+    }
+
+    /// Applies client defaults, e.g. the default proxy version, to the method.
+    pub fn with_defaults<'a>(&self, method: &'a ApiMethod) -> Cow<'a, ApiMethod> {
+        self.default_version
+            .as_ref()
+            .map_or(Cow::Borrowed(method), |version| {
+                Cow::Owned(method.clone().with_default_version(version))
+            })
+    }
+
+    /// URL to send the method to, defaults must be applied already.
+    ///
+    /// # Errors
+    /// - [`ApiError::RequestTooLong`] if the URL exceeds the allowed length.
+    pub fn request_url(&self, method: &ApiMethod) -> ApiResult<String> {
+        let url = String::from(build_request_url(
+            &self.base_url,
+            self.api_key.as_str(),
+            method,
+        ));
+        if url.len() > self.max_url_length {
+            return Err(ApiError::RequestTooLong { length: url.len() });
+        }
+
+        Ok(url)
+    }
+
+    /// How long to wait before sending the next request, `None` without a rate limit.
+    pub fn rate_limit_delay(&self) -> Option<Duration> {
+        self.rate_limiter
+            .as_ref()
+            .map(|rate_limiter| rate_limiter.reserve())
+    }
+
+    /// Delay before retrying the failed `attempt`, `None` if the result is final.
+    ///
+    /// Only [idempotent](ApiMethod::is_idempotent) methods are retried.
+    pub const fn retry_delay<R>(
+        &self,
+        method: &ApiMethod,
+        result: &ApiResult<R>,
+        attempt: u32,
+    ) -> Option<Duration> {
+        if !method.is_idempotent() {
+            return None;
+        }
+
+        match result {
+            Err(ApiError::TooManyRequests { .. }) if attempt < self.max_retries => Some(
+                self.retry_backoff
+                    .saturating_mul(2_u32.saturating_pow(attempt)),
+            ),
+            _ => None,
+        }
+    }
+
+    /// Reports the finished call to the [`RequestHook`], if any.
+    pub fn report<R>(&self, method: &ApiMethod, started: Instant, result: &ApiResult<R>) {
+        if let Some(on_request) = &self.on_request {
+            on_request.call(method.name(), started.elapsed(), result);
+        }
+    }
+
+    pub fn transport_error(&self, err: TransportError) -> ApiError {
+        transport::into_api_error(err, self.api_key.as_str())
+    }
+
+    /// Version `get_country` is cached under, the one sent after applying defaults.
+    pub fn country_cache_key(&self, params: &params::GetCountry) -> Option<ProxyVersion> {
+        params
+            .version
+            .clone()
+            .or_else(|| self.default_version.clone())
+    }
+
+    pub fn cached_country(&self, version: Option<&ProxyVersion>) -> Option<response::GetCountry> {
+        self.country_cache
+            .as_ref()
+            .and_then(|cache| cache.get(version))
+    }
+
+    pub fn cache_country(&self, version: Option<ProxyVersion>, response: &response::GetCountry) {
+        if let Some(cache) = &self.country_cache {
+            cache.insert(version, response.clone());
+        }
+    }
+
+    /// Masks the secrets of the method params and the API key in the text, e.g. the request URL, so it can be logged.
+    ///
+    /// The key is masked the same way as [`crate::mask_api_key`].
+    #[cfg(feature = "tracing")]
+    pub fn redact(&self, method: &ApiMethod, text: &str) -> String {
+        let params = method.get_params();
+        let (query, redacted_query) = (params.to_query_string(), params.to_redacted_query_string());
+        let text = if query == redacted_query {
+            text.to_string()
+        } else {
+            text.replace(&query, &redacted_query)
+        };
+
+        let api_key = self.api_key.as_str();
+        text.replace(api_key, &crate::mask_api_key(api_key))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::{ProxyCount, ProxyPeriod, transport::MockTransport};
+
+    fn core(options: CoreOptions) -> ClientCore<MockTransport> {
+        ClientCore::new(
+            Arc::new(MockTransport::new()),
+            CoreOptions {
+                api_key: Some(ApiKey::from("test-api-key")),
+                ..options
+            },
+        )
+        .unwrap()
+    }
+
+    fn get_price() -> ApiMethod {
+        ApiMethod::GetPrice(params::GetPrice {
+            count: ProxyCount::new(1).unwrap(),
+            period: ProxyPeriod::new(30).unwrap(),
+            version: None,
+        })
+    }
+
+    #[test]
+    fn test_new_defaults() {
+        let core = core(CoreOptions::default());
+
+        assert_eq!(core.base_url.as_str(), "https://px6.link/");
+        assert_eq!(core.max_retries, 0);
+        assert_eq!(core.retry_backoff, DEFAULT_RETRY_BACKOFF);
+        assert_eq!(core.max_url_length, DEFAULT_MAX_URL_LENGTH);
+        assert!(core.rate_limit_delay().is_none());
+    }
+
+    #[test]
+    fn test_new_without_api_key_error() {
+        let result = ClientCore::new(Arc::new(MockTransport::new()), CoreOptions::default());
+
+        assert!(matches!(result, Err(ClientBuildError::ApiKeyMustBeSet)));
+    }
+
+    #[test]
+    fn test_build_url_applies_default_version() {
+        let core = core(CoreOptions {
+            default_version: Some(ProxyVersion::Ipv4),
+            ..CoreOptions::default()
+        });
+
+        assert_eq!(
+            core.build_url(&get_price()),
+            "https://px6.link/api/test-api-key/getprice?count=1&period=30&version=4&format=json"
+        );
+    }
+
+    #[test]
+    fn test_request_url_too_long_error() {
+        let core = core(CoreOptions {
+            max_url_length: Some(10),
+            ..CoreOptions::default()
+        });
+
+        assert!(matches!(
+            core.request_url(&get_price()),
+            Err(ApiError::RequestTooLong { .. })
+        ));
+    }
+
+    #[test]
+    fn test_retry_delay_backs_off_on_too_many_requests_only() {
+        let core = core(CoreOptions {
+            max_retries: Some(2),
+            retry_backoff: Some(Duration::from_millis(100)),
+            ..CoreOptions::default()
+        });
+        let too_many: ApiResult<()> = Err(ApiError::TooManyRequests {
+            response: String::new(),
+        });
+
+        assert_eq!(
+            core.retry_delay(&get_price(), &too_many, 1),
+            Some(Duration::from_millis(200))
+        );
+        assert_eq!(core.retry_delay(&get_price(), &too_many, 2), None);
+        assert_eq!(core.retry_delay(&get_price(), &Ok(()), 0), None);
+    }
+
+    #[test]
+    fn test_retry_delay_never_retries_writes() {
+        let core = core(CoreOptions {
+            max_retries: Some(2),
+            ..CoreOptions::default()
+        });
+        let too_many: ApiResult<()> = Err(ApiError::TooManyRequests {
+            response: String::new(),
+        });
+        let buy = ApiMethod::Buy(params::Buy::new(
+            ProxyCount::new(1).unwrap(),
+            ProxyPeriod::new(30).unwrap(),
+            crate::Country::new("us").unwrap(),
+        ));
+
+        assert_eq!(core.retry_delay(&buy, &too_many, 0), None);
+    }
+
+    #[cfg(feature = "tracing")]
+    #[test]
+    fn test_redact_masks_api_key() {
+        let core = core(CoreOptions::default());
+
+        assert_eq!(
+            core.redact(&get_price(), "https://px6.link/api/test-api-key/getprice"),
+            "https://px6.link/api/te***/getprice"
+        );
+    }
+
+    #[cfg(feature = "tracing")]
+    #[test]
+    fn test_redact_masks_proxy_password() {
+        let core = core(CoreOptions::default());
+        let check = ApiMethod::Check(params::Check::by_proxy_string(
+            crate::ProxyString::new("127.0.0.1:8080:user:secret").unwrap(),
+        ));
+
+        assert_eq!(
+            core.redact(&check, &core.build_url(&check)),
+            "https://px6.link/api/te***/check?proxy=127.0.0.1:8080:user:***&format=json"
+        );
+    }
+}
@@ -1,6 +1,8 @@
-use serde::Deserializer;
+use serde::{Deserializer, Serializer};
 use std::str::FromStr;
 
+use crate::value_object::{DateTime, ProxyType};
+
 fn to_number<'de, D, T: FromStr>(deserializer: D) -> Result<T, D::Error>
 where
     D: Deserializer<'de>,
@@ -81,6 +83,48 @@ where
     }
 }
 
+#[allow(
+    clippy::trivially_copy_pass_by_ref,
+    reason = "signature required by `serialize_with`"
+)]
+pub fn serialize_proxy_status<S>(status: &bool, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    serializer.serialize_str(if *status { "1" } else { "0" })
+}
+
+pub fn parse_optional_proxy_status<'de, D>(deserializer: D) -> Result<Option<bool>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    use serde::{Deserialize, de::Error};
+    use serde_json::Value;
+
+    match Value::deserialize(deserializer)? {
+        Value::Null => Ok(None),
+        value => parse_proxy_status(value).map(Some).map_err(Error::custom),
+    }
+}
+
+#[allow(
+    clippy::ref_option,
+    clippy::trivially_copy_pass_by_ref,
+    reason = "signature required by `serialize_with`"
+)]
+pub fn serialize_optional_proxy_status<S>(
+    status: &Option<bool>,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    match status {
+        Some(status) => serialize_proxy_status(status, serializer),
+        None => serializer.serialize_none(),
+    }
+}
+
 pub fn to_string<'de, D>(deserializer: D) -> Result<String, D::Error>
 where
     D: Deserializer<'de>,
@@ -100,6 +144,109 @@ where
     }
 }
 
+pub fn parse_px6_datetime<'de, D>(deserializer: D) -> Result<DateTime, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    use serde::{Deserialize, de::Error, de::Unexpected};
+
+    let raw = String::deserialize(deserializer)?;
+
+    DateTime::new(raw.as_str())
+        .map_err(|_| Error::invalid_value(Unexpected::Str(&raw), &"date in `Y-m-d H:i:s` format"))
+}
+
+/// Response-side [`ProxyType`], falling back to [`ProxyType::Unknown`] instead of failing the whole response.
+pub fn lenient_proxy_type<'de, D>(deserializer: D) -> Result<ProxyType, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    use serde::Deserialize;
+
+    let raw = String::deserialize(deserializer)?;
+
+    Ok(match raw.as_str() {
+        "http" => ProxyType::Http,
+        "socks" => ProxyType::Socks5,
+        _ => ProxyType::Unknown(raw),
+    })
+}
+
+/// Items of a px6 list, in the order the API sent them.
+///
+/// Read with a visitor rather than through [`serde_json::Value`], whose map sorts the keys of keyed lists.
+fn list_items<'de, D>(deserializer: D) -> Result<Vec<serde_json::Value>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    use serde::de::{Error, MapAccess, SeqAccess, Unexpected, Visitor};
+    use serde_json::Value;
+
+    struct ListItems;
+
+    impl<'de> Visitor<'de> for ListItems {
+        type Value = Vec<Value>;
+
+        fn expecting(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.write_str("an array, an object keyed by id or an empty string")
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+            let mut items = Vec::with_capacity(seq.size_hint().unwrap_or_default());
+            while let Some(item) = seq.next_element()? {
+                items.push(item);
+            }
+            Ok(items)
+        }
+
+        // px6 often returns lists as an object keyed by item id
+        fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+            let mut items = Vec::with_capacity(map.size_hint().unwrap_or_default());
+            while let Some((key, mut item)) = map.next_entry::<String, Value>()? {
+                if let Value::Object(fields) = &mut item {
+                    fields.entry("id").or_insert(Value::String(key));
+                }
+                items.push(item);
+            }
+            Ok(items)
+        }
+
+        fn visit_str<E: Error>(self, value: &str) -> Result<Self::Value, E> {
+            if value.is_empty() {
+                Ok(Vec::new())
+            } else {
+                Err(E::invalid_value(Unexpected::Str(value), &self))
+            }
+        }
+    }
+
+    deserializer.deserialize_any(ListItems)
+}
+
+pub fn empty_or_vec<'de, D, T>(deserializer: D) -> Result<Vec<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: serde::de::DeserializeOwned,
+{
+    use serde::de::Error;
+
+    list_items(deserializer)?
+        .into_iter()
+        .map(|item| serde_json::from_value(item).map_err(Error::custom))
+        .collect()
+}
+
+pub fn lenient_vec<'de, D, T>(deserializer: D) -> Result<Vec<Result<T, String>>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: serde::de::DeserializeOwned,
+{
+    Ok(list_items(deserializer)?
+        .into_iter()
+        .map(|item| serde_json::from_value(item).map_err(|e| e.to_string()))
+        .collect())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -129,6 +276,30 @@ mod tests {
         value: String,
     }
 
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct TestStructDateTime {
+        #[serde(deserialize_with = "parse_px6_datetime")]
+        value: DateTime,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct TestStructList {
+        #[serde(deserialize_with = "empty_or_vec")]
+        value: Vec<u16>,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct TestStructLenientList {
+        #[serde(deserialize_with = "lenient_vec")]
+        value: Vec<Result<u16, String>>,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct TestStructOptionalStatus {
+        #[serde(default, deserialize_with = "parse_optional_proxy_status")]
+        status: Option<bool>,
+    }
+
     #[derive(Debug, Deserialize, PartialEq)]
     struct TestStructStatus {
         #[serde(deserialize_with = "parse_proxy_status")]
@@ -444,6 +615,135 @@ mod tests {
         assert!(result.is_err());
     }
 
+    // ===== empty_or_vec tests =====
+
+    #[test]
+    fn test_empty_or_vec_from_array() {
+        let json = r#"{"value": [1, 2, 3]}"#;
+        let result: TestStructList = serde_json::from_str(json).unwrap();
+        assert_eq!(result.value, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_empty_or_vec_from_empty_array() {
+        let json = r#"{"value": []}"#;
+        let result: TestStructList = serde_json::from_str(json).unwrap();
+        assert!(result.value.is_empty());
+    }
+
+    #[test]
+    fn test_empty_or_vec_from_empty_string() {
+        let json = r#"{"value": ""}"#;
+        let result: TestStructList = serde_json::from_str(json).unwrap();
+        assert!(result.value.is_empty());
+    }
+
+    #[test]
+    fn test_empty_or_vec_from_empty_object() {
+        let json = r#"{"value": {}}"#;
+        let result: TestStructList = serde_json::from_str(json).unwrap();
+        assert!(result.value.is_empty());
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct TestItem {
+        id: String,
+        value: u16,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct TestStructItems {
+        #[serde(deserialize_with = "empty_or_vec")]
+        value: Vec<TestItem>,
+    }
+
+    #[test]
+    fn test_empty_or_vec_from_object_keyed_by_id() {
+        let json = r#"{"value": {"1": {"id": "1", "value": 10}, "2": {"id": "2", "value": 20}}}"#;
+        let result: TestStructItems = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            result.value,
+            vec![
+                TestItem {
+                    id: "1".to_string(),
+                    value: 10
+                },
+                TestItem {
+                    id: "2".to_string(),
+                    value: 20
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_empty_or_vec_from_object_keeps_api_order() {
+        let json = r#"{"value": {"9": {"value": 90}, "10": {"value": 100}}}"#;
+        let result: TestStructItems = serde_json::from_str(json).unwrap();
+        let ids: Vec<&str> = result.value.iter().map(|item| item.id.as_str()).collect();
+        assert_eq!(ids, ["9", "10"]);
+    }
+
+    #[test]
+    fn test_empty_or_vec_from_object_fills_missing_id() {
+        let json = r#"{"value": {"7": {"value": 70}}}"#;
+        let result: TestStructItems = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            result.value,
+            vec![TestItem {
+                id: "7".to_string(),
+                value: 70
+            }]
+        );
+    }
+
+    #[test]
+    fn test_empty_or_vec_from_object_with_invalid_item() {
+        let json = r#"{"value": {"1": {"id": "1", "value": "x"}}}"#;
+        let result: Result<TestStructItems, _> = serde_json::from_str(json);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_empty_or_vec_from_non_empty_string() {
+        let json = r#"{"value": "1"}"#;
+        let result: Result<TestStructList, _> = serde_json::from_str(json);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_empty_or_vec_from_invalid_item() {
+        let json = r#"{"value": [true]}"#;
+        let result: Result<TestStructList, _> = serde_json::from_str(json);
+        assert!(result.is_err());
+    }
+
+    // ===== lenient_vec tests =====
+
+    #[test]
+    fn test_lenient_vec_keeps_valid_items() {
+        let json = r#"{"value": [1, "x", 3]}"#;
+        let result: TestStructLenientList = serde_json::from_str(json).unwrap();
+        assert_eq!(result.value.len(), 3);
+        assert_eq!(result.value[0], Ok(1));
+        assert!(result.value[1].is_err());
+        assert_eq!(result.value[2], Ok(3));
+    }
+
+    #[test]
+    fn test_lenient_vec_from_empty_string() {
+        let json = r#"{"value": ""}"#;
+        let result: TestStructLenientList = serde_json::from_str(json).unwrap();
+        assert!(result.value.is_empty());
+    }
+
+    #[test]
+    fn test_lenient_vec_from_non_list() {
+        let json = r#"{"value": 1}"#;
+        let result: Result<TestStructLenientList, _> = serde_json::from_str(json);
+        assert!(result.is_err());
+    }
+
     // ===== parse_proxy_status tests =====
 
     #[test]
@@ -522,4 +822,84 @@ mod tests {
         let result: Result<TestStructStatus, _> = serde_json::from_str(json);
         assert!(result.is_err());
     }
+
+    // ===== parse_optional_proxy_status tests =====
+
+    #[test]
+    fn test_parse_optional_proxy_status() {
+        let result: TestStructOptionalStatus = serde_json::from_str(r#"{"status": "1"}"#).unwrap();
+        assert_eq!(result.status, Some(true));
+
+        let result: TestStructOptionalStatus = serde_json::from_str(r#"{"status": null}"#).unwrap();
+        assert_eq!(result.status, None);
+
+        let result: TestStructOptionalStatus = serde_json::from_str("{}").unwrap();
+        assert_eq!(result.status, None);
+    }
+
+    #[test]
+    fn test_parse_optional_proxy_status_invalid() {
+        let result: Result<TestStructOptionalStatus, _> =
+            serde_json::from_str(r#"{"status": "2"}"#);
+        assert!(result.is_err());
+    }
+
+    // ===== parse_px6_datetime tests =====
+
+    #[test]
+    fn test_parse_px6_datetime_from_string() {
+        let json = r#"{"value": "2016-06-19 16:32:39"}"#;
+        let result: TestStructDateTime = serde_json::from_str(json).unwrap();
+        assert_eq!(result.value.as_str(), "2016-06-19 16:32:39");
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn test_parse_px6_datetime_parses_naive_datetime() {
+        let json = r#"{"value": "2016-06-19 16:32:39"}"#;
+        let result: TestStructDateTime = serde_json::from_str(json).unwrap();
+        let expected = chrono::NaiveDate::from_ymd_opt(2016, 6, 19)
+            .and_then(|date| date.and_hms_opt(16, 32, 39))
+            .unwrap();
+        assert_eq!(result.value.as_naive(), &expected);
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn test_parse_px6_datetime_from_string_invalid_format() {
+        let json = r#"{"value": "19.06.2016 16:32"}"#;
+        let result: Result<TestStructDateTime, _> = serde_json::from_str(json);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_parse_px6_datetime_from_number() {
+        let json = r#"{"value": 1466343159}"#;
+        let result: Result<TestStructDateTime, _> = serde_json::from_str(json);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_parse_px6_datetime_from_null() {
+        let json = r#"{"value": null}"#;
+        let result: Result<TestStructDateTime, _> = serde_json::from_str(json);
+        assert!(result.is_err());
+    }
+
+    // ===== serialize_proxy_status tests =====
+
+    #[derive(Debug, serde::Serialize)]
+    struct TestStructSerializeStatus {
+        #[serde(serialize_with = "serialize_proxy_status")]
+        status: bool,
+    }
+
+    #[test]
+    fn test_serialize_proxy_status_round_trip() {
+        for status in [true, false] {
+            let json = serde_json::to_string(&TestStructSerializeStatus { status }).unwrap();
+            let result: TestStructStatus = serde_json::from_str(&json).unwrap();
+            assert_eq!(result.status, status);
+        }
+    }
 }
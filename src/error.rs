@@ -1,26 +1,43 @@
+#[cfg(any(feature = "async_client", feature = "sync_client"))]
 use serde_json::Value;
 
 /// Errors that can be thrown by the API.
 #[derive(Debug, thiserror::Error)]
 pub enum ApiError {
     /// Error that exists in the [API documentation](https://px6.me/developers).
-    #[error("Documented error occurred: {code}, response body: {response}")]
+    #[error(
+        "Documented error occurred: {code}{}, response body: {response}",
+        .message.as_deref().map(|message| format!(" ({message})")).unwrap_or_default()
+    )]
     DocumentedError {
         code: DocumentedErrorCode,
+        /// Human-readable message from the `error` field of the response, if any.
+        message: Option<String>,
         response: String,
     },
 
     /// Any `reqwest` error: network error, ssl error, proxy error etc.
+    #[cfg(feature = "reqwest")]
     #[error("Reqwest error: {source}")]
     ReqwestError { source: reqwest::Error },
 
+    /// Error of a custom transport, see [`crate::transport`].
+    #[error("Transport error: {source}")]
+    TransportError {
+        source: crate::transport::TransportError,
+    },
+
     /// Throttling error. The API is allowed to do no more than 3 queries in 1 second.
     #[error("Too many requests: {response}")]
     TooManyRequests { response: String },
 
     /// API error that is not documented in the [API documentation](https://px6.me/developers).
-    #[error("Unknown API error: {response}")]
-    UnknownError { response: String },
+    #[error("Unknown API error with status {status}: {response}")]
+    UnknownError {
+        /// HTTP status code of the response, e.g. to tell a `503` outage from a `403` ban.
+        status: u16,
+        response: String,
+    },
 
     /// Success response but cannot parse body to structs.
     #[error("Success response but cannot parse body: {source}, response: {response}")]
@@ -28,6 +45,71 @@ pub enum ApiError {
         source: serde_json::Error,
         response: String,
     },
+
+    /// Request URL is longer than the client allows, e.g. because of too many proxy ids. The request is not sent.
+    #[error("Request URL is too long: {length} characters")]
+    RequestTooLong { length: usize },
+
+    /// Success response with an empty body.
+    #[error("Success response but body is empty")]
+    EmptyResponse,
+
+    /// Success response with a body that is not JSON at all, e.g. a maintenance HTML page.
+    #[error("Success response but body is not JSON: {response}")]
+    NonJsonResponse { response: String },
+}
+
+impl ApiError {
+    /// Whether the request may succeed if it is sent again.
+    ///
+    /// Only throttling, timeouts and connection failures are considered transient.
+    #[must_use]
+    #[cfg_attr(
+        not(feature = "reqwest"),
+        allow(
+            clippy::missing_const_for_fn,
+            reason = "only const without the `reqwest` error"
+        )
+    )]
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Self::TooManyRequests { .. } => true,
+            #[cfg(all(feature = "reqwest", not(target_arch = "wasm32")))]
+            Self::ReqwestError { source } => source.is_timeout() || source.is_connect(),
+            // Browsers do not tell connection failures apart from other fetch errors
+            #[cfg(all(feature = "reqwest", target_arch = "wasm32"))]
+            Self::ReqwestError { source } => source.is_timeout(),
+            Self::DocumentedError { .. }
+            | Self::UnknownError { .. }
+            | Self::SuccessButCannotParse { .. }
+            | Self::RequestTooLong { .. }
+            | Self::TransportError { .. }
+            | Self::EmptyResponse
+            | Self::NonJsonResponse { .. } => false,
+        }
+    }
+
+    /// Picks the most specific error for a success body that cannot be parsed to structs.
+    #[cfg(any(feature = "async_client", feature = "sync_client"))]
+    pub(crate) fn from_unparseable_body(source: serde_json::Error, response: String) -> Self {
+        if response.trim().is_empty() {
+            return Self::EmptyResponse;
+        }
+
+        if serde_json::from_str::<Value>(&response).is_err() {
+            return Self::NonJsonResponse { response };
+        }
+
+        Self::SuccessButCannotParse { source, response }
+    }
+}
+
+/// Error of an operation split into several requests, keeps responses of the requests sent before the failure.
+#[derive(Debug, thiserror::Error)]
+#[error("Batch failed after {} successful requests: {source}", completed.responses.len())]
+pub struct BatchError<T> {
+    pub completed: crate::response::Batch<T>,
+    pub source: ApiError,
 }
 
 /// Error that exists in the [API documentation](https://px6.me/developers).
@@ -107,6 +189,7 @@ pub enum DocumentedErrorCode {
 }
 
 impl DocumentedErrorCode {
+    #[cfg(any(feature = "async_client", feature = "sync_client"))]
     const fn from_numeric_code(code: usize) -> Option<Self> {
         Some(match code {
             30 => Self::Unknown,
@@ -130,10 +213,34 @@ impl DocumentedErrorCode {
         })
     }
 
+    /// Returns the numeric code from the [API documentation](https://px6.me/developers), e.g. `100` for [`Self::Key`].
+    #[must_use]
+    pub const fn code(&self) -> usize {
+        match self {
+            Self::Unknown => 30,
+            Self::Key => 100,
+            Self::Ip => 105,
+            Self::Method => 110,
+            Self::Count => 200,
+            Self::Period => 210,
+            Self::Country => 220,
+            Self::Ids => 230,
+            Self::Version => 240,
+            Self::Description => 250,
+            Self::Type => 260,
+            Self::Port => 270,
+            Self::ProxyString => 280,
+            Self::ActiveProxyAllow => 300,
+            Self::NoMoney => 400,
+            Self::NotFound => 404,
+            Self::Price => 410,
+        }
+    }
+
+    #[cfg(any(feature = "async_client", feature = "sync_client"))]
     pub(crate) fn parse_from_response_body(body: &str) -> Option<Self> {
         if let Ok(Value::Object(body_value)) = serde_json::from_str::<Value>(body)
-            && let Some(Value::Number(code)) = body_value.get("error_id")
-            && let Some(Ok(code)) = code.as_u64().map(usize::try_from) // cast Number to usize
+            && let Some(code) = body_value.get("error_id").and_then(Self::error_id_to_usize)
             && let Some(code) = Self::from_numeric_code(code)
         {
             return Some(code);
@@ -141,6 +248,29 @@ impl DocumentedErrorCode {
 
         None
     }
+
+    /// Accepts `error_id` both as a number and as a numeric string, e.g. `100` and `"100"`.
+    #[cfg(any(feature = "async_client", feature = "sync_client"))]
+    fn error_id_to_usize(error_id: &Value) -> Option<usize> {
+        match error_id {
+            Value::Number(code) => code.as_u64().and_then(|code| usize::try_from(code).ok()),
+            Value::String(code) => code.trim().parse().ok(),
+            _ => None,
+        }
+    }
+
+    /// Extracts the human-readable `error` message that accompanies `error_id`.
+    #[cfg(any(feature = "async_client", feature = "sync_client"))]
+    pub(crate) fn parse_message_from_response_body(body: &str) -> Option<String> {
+        if let Ok(Value::Object(body_value)) = serde_json::from_str::<Value>(body)
+            && let Some(Value::String(message)) = body_value.get("error")
+            && !message.is_empty()
+        {
+            return Some(message.clone());
+        }
+
+        None
+    }
 }
 
 #[cfg(test)]
@@ -149,6 +279,7 @@ mod tests {
 
     use super::*;
 
+    #[cfg(any(feature = "async_client", feature = "sync_client"))]
     #[test]
     fn test_documented_error_code_from_numeric_code() {
         // Test all known error codes
@@ -227,6 +358,37 @@ mod tests {
         assert_eq!(DocumentedErrorCode::from_numeric_code(50), None);
     }
 
+    #[cfg(any(feature = "async_client", feature = "sync_client"))]
+    #[test]
+    fn test_documented_error_code_code() {
+        let codes = [
+            (DocumentedErrorCode::Unknown, 30),
+            (DocumentedErrorCode::Key, 100),
+            (DocumentedErrorCode::Ip, 105),
+            (DocumentedErrorCode::Method, 110),
+            (DocumentedErrorCode::Count, 200),
+            (DocumentedErrorCode::Period, 210),
+            (DocumentedErrorCode::Country, 220),
+            (DocumentedErrorCode::Ids, 230),
+            (DocumentedErrorCode::Version, 240),
+            (DocumentedErrorCode::Description, 250),
+            (DocumentedErrorCode::Type, 260),
+            (DocumentedErrorCode::Port, 270),
+            (DocumentedErrorCode::ProxyString, 280),
+            (DocumentedErrorCode::ActiveProxyAllow, 300),
+            (DocumentedErrorCode::NoMoney, 400),
+            (DocumentedErrorCode::NotFound, 404),
+            (DocumentedErrorCode::Price, 410),
+        ];
+
+        for (variant, code) in codes {
+            assert_eq!(variant.code(), code);
+            // code() and from_numeric_code() must stay in sync
+            assert_eq!(DocumentedErrorCode::from_numeric_code(code), Some(variant));
+        }
+    }
+
+    #[cfg(any(feature = "async_client", feature = "sync_client"))]
     #[test]
     fn test_parse_from_response_body_valid() {
         // Test valid JSON with error_id field
@@ -255,6 +417,33 @@ mod tests {
         );
     }
 
+    #[cfg(any(feature = "async_client", feature = "sync_client"))]
+    #[test]
+    fn test_parse_from_response_body_string_error_id() {
+        let body = r#"{"error_id": "100"}"#;
+        assert_eq!(
+            DocumentedErrorCode::parse_from_response_body(body),
+            Some(DocumentedErrorCode::Key)
+        );
+
+        let body = r#"{"status": "no", "error_id": "404", "error": "Not found"}"#;
+        assert_eq!(
+            DocumentedErrorCode::parse_from_response_body(body),
+            Some(DocumentedErrorCode::NotFound)
+        );
+
+        // Test string error_id that is unknown or negative
+        assert_eq!(
+            DocumentedErrorCode::parse_from_response_body(r#"{"error_id": "999"}"#),
+            None
+        );
+        assert_eq!(
+            DocumentedErrorCode::parse_from_response_body(r#"{"error_id": "-100"}"#),
+            None
+        );
+    }
+
+    #[cfg(any(feature = "async_client", feature = "sync_client"))]
     #[test]
     fn test_parse_from_response_body_invalid() {
         // Test invalid JSON
@@ -291,19 +480,56 @@ mod tests {
         );
     }
 
+    #[cfg(any(feature = "async_client", feature = "sync_client"))]
+    #[test]
+    fn test_parse_message_from_response_body() {
+        let body = r#"{"status": "no", "error_id": 200, "error": "Wrong count"}"#;
+        assert_eq!(
+            DocumentedErrorCode::parse_message_from_response_body(body),
+            Some("Wrong count".to_string())
+        );
+
+        // Test missing, empty and non-string messages
+        for body in [
+            r#"{"error_id": 200}"#,
+            r#"{"error_id": 200, "error": ""}"#,
+            r#"{"error_id": 200, "error": 1}"#,
+            "invalid json",
+        ] {
+            assert_eq!(
+                DocumentedErrorCode::parse_message_from_response_body(body),
+                None
+            );
+        }
+    }
+
     #[test]
     fn test_api_error_display() {
         // Test DocumentedError display
         let err = ApiError::DocumentedError {
             code: DocumentedErrorCode::Key,
+            message: None,
             response: "Authorization failed".to_string(),
         };
         assert!(err.to_string().contains("Authorization error, wrong key"));
         assert!(err.to_string().contains("Authorization failed"));
+        assert!(!err.to_string().contains("()"));
+
+        // Test DocumentedError with message
+        let err = ApiError::DocumentedError {
+            code: DocumentedErrorCode::Count,
+            message: Some("Wrong count".to_string()),
+            response: "{}".to_string(),
+        };
+        assert_eq!(
+            err.to_string(),
+            "Documented error occurred: Wrong proxies quantity, wrong amount or no quantity input (Wrong count), response body: {}"
+        );
 
         // Test DocumentedError with different code
         let err = ApiError::DocumentedError {
             code: DocumentedErrorCode::NoMoney,
+            message: None,
             response: "Low balance".to_string(),
         };
         assert!(
@@ -314,9 +540,13 @@ mod tests {
 
         // Test UnknownError display
         let err = ApiError::UnknownError {
+            status: 500,
             response: "Something went wrong".to_string(),
         };
-        assert!(err.to_string().contains("Unknown API error"));
+        assert!(
+            err.to_string()
+                .contains("Unknown API error with status 500")
+        );
         assert!(err.to_string().contains("Something went wrong"));
 
         // Test TooManyRequests display
@@ -339,6 +569,96 @@ mod tests {
         assert!(err.to_string().contains("invalid response"));
     }
 
+    #[test]
+    fn test_is_retryable_too_many_requests() {
+        let err = ApiError::TooManyRequests {
+            response: String::new(),
+        };
+        assert!(err.is_retryable());
+    }
+
+    #[cfg(feature = "reqwest")]
+    #[tokio::test]
+    async fn test_is_retryable_connection_error() {
+        // nothing listens on a just released port
+        let address = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        let source = reqwest::get(format!("http://{address}")).await.unwrap_err();
+
+        assert!(ApiError::ReqwestError { source }.is_retryable());
+    }
+
+    #[cfg(feature = "reqwest")]
+    #[test]
+    fn test_is_retryable_reqwest_builder_error() {
+        let source = reqwest::Client::new().get("not a url").build().unwrap_err();
+        assert!(!ApiError::ReqwestError { source }.is_retryable());
+    }
+
+    #[test]
+    fn test_is_retryable_terminal_errors() {
+        let err = ApiError::DocumentedError {
+            code: DocumentedErrorCode::Key,
+            message: None,
+            response: String::new(),
+        };
+        assert!(!err.is_retryable());
+
+        let err = ApiError::UnknownError {
+            status: 500,
+            response: String::new(),
+        };
+        assert!(!err.is_retryable());
+
+        let source = serde_json::from_str::<serde_json::Value>("invalid").unwrap_err();
+        let err = ApiError::SuccessButCannotParse {
+            source,
+            response: String::new(),
+        };
+        assert!(!err.is_retryable());
+
+        assert!(!ApiError::EmptyResponse.is_retryable());
+        assert!(!ApiError::RequestTooLong { length: 10_000 }.is_retryable());
+        assert!(
+            !ApiError::NonJsonResponse {
+                response: String::new()
+            }
+            .is_retryable()
+        );
+    }
+
+    #[cfg(any(feature = "async_client", feature = "sync_client"))]
+    #[test]
+    fn test_from_unparseable_body_empty() {
+        for body in ["", "  \n"] {
+            let source = serde_json::from_str::<Value>(body).unwrap_err();
+            let err = ApiError::from_unparseable_body(source, body.to_string());
+            assert!(matches!(err, ApiError::EmptyResponse));
+        }
+    }
+
+    #[cfg(any(feature = "async_client", feature = "sync_client"))]
+    #[test]
+    fn test_from_unparseable_body_html() {
+        let body = "<html><body>Maintenance</body></html>";
+        let source = serde_json::from_str::<Value>(body).unwrap_err();
+        let err = ApiError::from_unparseable_body(source, body.to_string());
+        assert!(matches!(err, ApiError::NonJsonResponse { response } if response == body));
+    }
+
+    #[cfg(any(feature = "async_client", feature = "sync_client"))]
+    #[test]
+    fn test_from_unparseable_body_unexpected_json() {
+        let body = r#"{"status": "yes"}"#;
+        let source = serde_json::from_str::<Vec<u8>>(body).unwrap_err();
+        let err = ApiError::from_unparseable_body(source, body.to_string());
+        assert!(
+            matches!(err, ApiError::SuccessButCannotParse { response, .. } if response == body)
+        );
+    }
+
     #[test]
     fn test_documented_error_code_display() {
         // Test error messages for all documented error codes
@@ -1,24 +1,369 @@
+#![cfg_attr(
+    target_arch = "wasm32",
+    allow(
+        clippy::future_not_send,
+        reason = "browser requests are bound to their thread, so futures cannot be `Send`"
+    )
+)]
+
+#[cfg(feature = "async_client")]
+pub use api::{ApiFuture, Proxy6Api};
 #[cfg(feature = "async_client")]
 pub use async_client::*;
+pub use method::{ApiMethod, MethodName, RawParams};
 #[cfg(feature = "sync_client")]
 pub use sync_client::*;
 pub use value_object::*;
 
+#[cfg(feature = "async_client")]
+mod api;
 #[cfg(feature = "async_client")]
 mod async_client;
+#[cfg(any(feature = "async_client", feature = "sync_client"))]
+mod cache;
+#[cfg(any(feature = "async_client", feature = "sync_client"))]
+mod client_core;
 pub(crate) mod deserializer;
 pub mod error;
 mod method;
 pub mod params;
+#[cfg(any(feature = "async_client", feature = "sync_client"))]
+mod rate_limit;
 pub mod response;
 #[cfg(feature = "sync_client")]
 mod sync_client;
+#[cfg(any(feature = "async_client", feature = "sync_client"))]
+mod time;
+pub mod transport;
 mod value_object;
 
+/// Base URL the client builders use when no `base_url` is set.
+pub const DEFAULT_BASE_URL: &str = "https://px6.link";
+
+/// Path of every API method relative to the base URL, `{api_key}` and `{method}` are substituted per request.
+pub const API_PATH_TEMPLATE: &str = "/api/{api_key}/{method}";
+
+/// Environment variable the client builders read the API key from by default.
+pub const API_KEY_ENV_VAR: &str = "PROXY6_API_KEY";
+
 #[derive(Debug, thiserror::Error)]
 pub enum ClientBuildError {
     #[error("API key must be set")]
     ApiKeyMustBeSet,
+    #[error("API key is invalid: {source}")]
+    InvalidApiKey { source: BuildError },
+    #[error("Base URL `{url}` is not a valid http(s) URL")]
+    InvalidBaseUrl { url: String },
+    #[error("Rate limit must allow at least one request")]
+    RateLimitTooLow,
+    #[error("Concurrency limit must allow at least one request")]
+    ConcurrencyLimitTooLow,
+    /// Only returned without the `reqwest` feature, the builder has no HTTP client to fall back to.
+    #[error("Transport must be set when the `reqwest` feature is disabled")]
+    TransportMustBeSet,
+    #[cfg(feature = "reqwest")]
+    #[error("Cannot build HTTP client: {source}")]
+    ReqwestError { source: reqwest::Error },
 }
 
 pub type ApiResult<T> = Result<T, error::ApiError>;
+
+#[cfg(any(feature = "async_client", feature = "sync_client"))]
+type RequestHookFn =
+    dyn Fn(MethodName, std::time::Duration, Result<(), &error::ApiError>) + Send + Sync;
+
+/// Callback invoked after every API call with the method name, elapsed time (retries included) and outcome.
+#[cfg(any(feature = "async_client", feature = "sync_client"))]
+#[derive(Clone)]
+pub(crate) struct RequestHook(std::sync::Arc<RequestHookFn>);
+
+#[cfg(any(feature = "async_client", feature = "sync_client"))]
+impl RequestHook {
+    pub(crate) fn new(
+        hook: impl Fn(MethodName, std::time::Duration, Result<(), &error::ApiError>)
+        + Send
+        + Sync
+        + 'static,
+    ) -> Self {
+        Self(std::sync::Arc::new(hook))
+    }
+
+    pub(crate) fn call<T>(
+        &self,
+        method: MethodName,
+        elapsed: std::time::Duration,
+        result: &ApiResult<T>,
+    ) {
+        (self.0)(method, elapsed, result.as_ref().map(|_| ()));
+    }
+}
+
+#[cfg(any(feature = "async_client", feature = "sync_client"))]
+impl std::fmt::Debug for RequestHook {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("RequestHook")
+    }
+}
+
+/// Query param asking the API for JSON output whatever the account default format is.
+#[cfg(any(feature = "async_client", feature = "sync_client"))]
+const FORMAT_JSON_PARAM: &str = "format=json";
+
+/// Delay between requests sent in a row by a client without `rate_limit`, the API allows 3 requests per second.
+#[cfg(any(feature = "async_client", feature = "sync_client"))]
+const API_REQUEST_SPACING: std::time::Duration = std::time::Duration::from_millis(334);
+
+/// Builds the full request URL of the method following [`API_PATH_TEMPLATE`], always requesting JSON output.
+///
+/// The API path is appended to the path of `base_url` and params to its query, if any.
+#[cfg(any(feature = "async_client", feature = "sync_client"))]
+pub(crate) fn build_request_url(
+    base_url: &url::Url,
+    api_key: &str,
+    method: &method::ApiMethod,
+) -> url::Url {
+    let mut url = base_url.clone();
+    if let Ok(mut segments) = url.path_segments_mut() {
+        segments
+            .pop_if_empty()
+            .extend(["api", api_key, &method.to_string()]);
+    }
+
+    let params = method.get_params().to_query_string();
+    let query = [url.query().unwrap_or_default(), &params, FORMAT_JSON_PARAM]
+        .into_iter()
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("&");
+    url.set_query(Some(&query));
+
+    url
+}
+
+/// Parses the base URL set on a client builder, trailing slashes are trimmed so `https://px6.link/` works too.
+#[cfg(any(feature = "async_client", feature = "sync_client"))]
+pub(crate) fn parse_base_url(base_url: Option<String>) -> Result<url::Url, ClientBuildError> {
+    let base_url = base_url.unwrap_or_else(|| DEFAULT_BASE_URL.to_string());
+
+    match url::Url::parse(base_url.trim_end_matches('/')) {
+        Ok(url) if matches!(url.scheme(), "http" | "https") => Ok(url),
+        _ => Err(ClientBuildError::InvalidBaseUrl { url: base_url }),
+    }
+}
+
+/// Classifies the HTTP response of any method, shared by both clients.
+///
+/// Documented errors win over the status code, as the API may send them with `200 OK`.
+#[cfg(any(feature = "async_client", feature = "sync_client"))]
+pub(crate) fn parse_response<TResponse: serde::de::DeserializeOwned>(
+    response: transport::HttpResponse,
+) -> ApiResult<response::RawResponse<TResponse>> {
+    let transport::HttpResponse { status, body } = response;
+
+    // 429 Too Many Requests
+    if status == 429 {
+        return Err(error::ApiError::TooManyRequests { response: body });
+    }
+
+    if let Some(code) = error::DocumentedErrorCode::parse_from_response_body(&body) {
+        return Err(error::ApiError::DocumentedError {
+            message: error::DocumentedErrorCode::parse_message_from_response_body(&body),
+            response: body,
+            code,
+        });
+    }
+
+    if !(200..300).contains(&status) {
+        return Err(error::ApiError::UnknownError {
+            status,
+            response: body,
+        });
+    }
+
+    match serde_json::from_str(&body) {
+        Ok(data) => Ok(response::RawResponse { data, body }),
+        Err(err) => Err(error::ApiError::from_unparseable_body(err, body)),
+    }
+}
+
+/// Masks the API key for debug output, keeping only the first 2 characters of long keys.
+pub(crate) fn mask_api_key(api_key: &str) -> String {
+    if api_key.chars().count() <= 4 {
+        return "***".to_string();
+    }
+
+    format!("{}***", api_key.chars().take(2).collect::<String>())
+}
+
+/// Replaces the API key in the URL path of `reqwest` error, so the error can be safely logged.
+#[cfg(all(
+    feature = "reqwest",
+    any(feature = "async_client", feature = "sync_client")
+))]
+pub(crate) fn redact_reqwest_error(mut err: reqwest::Error, api_key: &str) -> reqwest::Error {
+    if !api_key.is_empty()
+        && let Some(url) = err.url_mut()
+    {
+        let path = url.path().replace(api_key, &mask_api_key(api_key));
+        url.set_path(&path);
+    }
+
+    err
+}
+
+#[cfg(all(test, any(feature = "async_client", feature = "sync_client")))]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_build_request_url() {
+        let method = method::ApiMethod::GetCountry(params::GetCountry {
+            version: Some(ProxyVersion::Ipv4),
+        });
+
+        assert_eq!(
+            build_request_url(&parse_base_url(None).unwrap(), "key", &method).as_str(),
+            "https://px6.link/api/key/getcountry?version=4&format=json"
+        );
+    }
+
+    #[test]
+    fn test_build_request_url_without_params() {
+        let method = method::ApiMethod::GetCountry(params::GetCountry { version: None });
+
+        assert_eq!(
+            build_request_url(&parse_base_url(None).unwrap(), "key", &method).as_str(),
+            "https://px6.link/api/key/getcountry?format=json"
+        );
+    }
+
+    #[test]
+    fn test_build_request_url_keeps_base_path_and_query() {
+        let method = method::ApiMethod::GetCountry(params::GetCountry {
+            version: Some(ProxyVersion::Ipv6),
+        });
+
+        for base_url in [
+            "https://example.com/px6?tenant=1",
+            "https://example.com/px6/?tenant=1",
+        ] {
+            let base_url = parse_base_url(Some(base_url.to_string())).unwrap();
+
+            assert_eq!(
+                build_request_url(&base_url, "key", &method).as_str(),
+                "https://example.com/px6/api/key/getcountry?tenant=1&version=6&format=json"
+            );
+        }
+    }
+
+    #[test]
+    fn test_build_request_url_with_trailing_slash() {
+        let method = method::ApiMethod::GetCountry(params::GetCountry { version: None });
+        let with_slash = parse_base_url(Some("https://px6.link/".to_string())).unwrap();
+        let without_slash = parse_base_url(Some("https://px6.link".to_string())).unwrap();
+
+        assert_eq!(
+            build_request_url(&with_slash, "key", &method),
+            build_request_url(&without_slash, "key", &method)
+        );
+    }
+
+    #[test]
+    fn test_parse_base_url_valid() {
+        assert_eq!(
+            parse_base_url(Some("http://localhost:8080".to_string()))
+                .unwrap()
+                .as_str(),
+            "http://localhost:8080/"
+        );
+    }
+
+    #[test]
+    fn test_parse_base_url_missing_scheme_error() {
+        assert!(matches!(
+            parse_base_url(Some("px6.link".to_string())),
+            Err(ClientBuildError::InvalidBaseUrl { url }) if url == "px6.link"
+        ));
+        assert!(matches!(
+            parse_base_url(Some("ftp://px6.link".to_string())),
+            Err(ClientBuildError::InvalidBaseUrl { .. })
+        ));
+    }
+
+    #[test]
+    fn test_parse_base_url_error() {
+        assert!(matches!(
+            parse_base_url(Some("not a url".to_string())),
+            Err(ClientBuildError::InvalidBaseUrl { url }) if url == "not a url"
+        ));
+    }
+
+    fn parse(status: u16, body: &str) -> ApiResult<response::RawResponse<response::Balance>> {
+        parse_response(transport::HttpResponse {
+            status,
+            body: body.to_string(),
+        })
+    }
+
+    #[test]
+    fn test_parse_response_documented_error_with_ok_status() {
+        let result = parse(
+            200,
+            r#"{"status":"no","error_id":400,"error":"Error no money"}"#,
+        );
+
+        assert!(matches!(
+            result,
+            Err(error::ApiError::DocumentedError {
+                code: error::DocumentedErrorCode::NoMoney,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_parse_response_documented_error_with_error_status() {
+        let result = parse(500, r#"{"status":"no","error_id":"400"}"#);
+
+        assert!(matches!(
+            result,
+            Err(error::ApiError::DocumentedError {
+                code: error::DocumentedErrorCode::NoMoney,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_parse_response_status() {
+        assert!(matches!(
+            parse(429, ""),
+            Err(error::ApiError::TooManyRequests { .. })
+        ));
+        assert!(matches!(
+            parse(502, "Bad Gateway"),
+            Err(error::ApiError::UnknownError { status: 502, .. })
+        ));
+        assert_eq!(
+            parse(200, r#"{"user_id":"1","balance":"48.80","currency":"RUB"}"#)
+                .unwrap()
+                .data
+                .currency
+                .as_str(),
+            "RUB"
+        );
+    }
+
+    #[test]
+    fn test_mask_api_key() {
+        assert_eq!(mask_api_key("abcdef123456"), "ab***");
+    }
+
+    #[test]
+    fn test_mask_api_key_short() {
+        assert_eq!(mask_api_key("abcd"), "***");
+        assert_eq!(mask_api_key(""), "***");
+    }
+}
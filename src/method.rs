@@ -1,7 +1,55 @@
 use std::fmt::Display;
 
+#[cfg(any(feature = "async_client", feature = "sync_client"))]
+use crate::ProxyVersion;
 use crate::params;
 
+/// Name of an API method, e.g. to label logs or metrics.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MethodName {
+    GetPrice,
+    GetCount,
+    GetCountry,
+    GetProxy,
+    SetType,
+    SetDescription,
+    Buy,
+    Prolong,
+    Delete,
+    Check,
+    IpAuth,
+    /// Method called by name with `call_raw`, the actual endpoint is not known to this enum.
+    Raw,
+}
+
+impl MethodName {
+    /// Endpoint of the method, as used in the request URL.
+    #[must_use]
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::GetPrice => "getprice",
+            Self::GetCount => "getcount",
+            Self::GetCountry => "getcountry",
+            Self::GetProxy => "getproxy",
+            Self::SetType => "settype",
+            Self::SetDescription => "setdescr",
+            Self::Buy => "buy",
+            Self::Prolong => "prolong",
+            Self::Delete => "delete",
+            Self::Check => "check",
+            Self::IpAuth => "ipauth",
+            Self::Raw => "raw",
+        }
+    }
+}
+
+impl Display for MethodName {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.as_str().fmt(f)
+    }
+}
+
+/// API method together with its params, e.g. to build the request URL with `build_url` of the clients.
 #[derive(Debug, Clone)]
 pub enum ApiMethod {
     GetPrice(params::GetPrice),
@@ -15,10 +63,28 @@ pub enum ApiMethod {
     Delete(params::Delete),
     Check(params::Check),
     IpAuth(params::IpAuth),
+    Raw(RawParams),
+}
+
+/// Method name and params of a call not modeled by this crate.
+#[derive(Debug, Clone)]
+pub struct RawParams {
+    pub method: String,
+    pub params: Vec<(String, String)>,
+}
+
+impl params::ApiParams for RawParams {
+    fn to_query_tuple(&self) -> Vec<(&str, Option<String>)> {
+        self.params
+            .iter()
+            .map(|(key, value)| (key.as_str(), Some(value.clone())))
+            .collect()
+    }
 }
 
 impl ApiMethod {
-    pub fn get_params(&self) -> &dyn params::ApiParams {
+    #[cfg(any(feature = "async_client", feature = "sync_client"))]
+    pub(crate) fn get_params(&self) -> &dyn params::ApiParams {
         match self {
             Self::GetPrice(params) => params,
             Self::GetCount(params) => params,
@@ -31,26 +97,78 @@ impl ApiMethod {
             Self::Delete(params) => params,
             Self::Check(params) => params,
             Self::IpAuth(params) => params,
+            Self::Raw(params) => params,
+        }
+    }
+
+    #[must_use]
+    pub const fn name(&self) -> MethodName {
+        match self {
+            Self::GetPrice(_) => MethodName::GetPrice,
+            Self::GetCount(_) => MethodName::GetCount,
+            Self::GetCountry(_) => MethodName::GetCountry,
+            Self::GetProxy(_) => MethodName::GetProxy,
+            Self::SetType(_) => MethodName::SetType,
+            Self::SetDescription(_) => MethodName::SetDescription,
+            Self::Buy(_) => MethodName::Buy,
+            Self::Prolong(_) => MethodName::Prolong,
+            Self::Delete(_) => MethodName::Delete,
+            Self::Check(_) => MethodName::Check,
+            Self::IpAuth(_) => MethodName::IpAuth,
+            Self::Raw(_) => MethodName::Raw,
         }
     }
+
+    /// Whether the method only reads data, so sending it twice has the same effect as sending it once.
+    ///
+    /// Clients retry only idempotent methods: a retried `buy` or `prolong` could charge the balance twice.
+    /// Every method that changes proxies or settings, and any [`Self::Raw`] call, is treated as not idempotent.
+    #[must_use]
+    pub const fn is_idempotent(&self) -> bool {
+        matches!(
+            self,
+            Self::GetPrice(_)
+                | Self::GetCount(_)
+                | Self::GetCountry(_)
+                | Self::GetProxy(_)
+                | Self::Check(_)
+        )
+    }
+}
+
+impl ApiMethod {
+    /// Fills the unset `version` of params that have one, explicitly set version always wins.
+    #[cfg(any(feature = "async_client", feature = "sync_client"))]
+    pub(crate) fn with_default_version(mut self, version: &ProxyVersion) -> Self {
+        if let Self::GetPrice(params::GetPrice {
+            version: params_version @ None,
+            ..
+        })
+        | Self::GetCount(params::GetCount {
+            version: params_version @ None,
+            ..
+        })
+        | Self::GetCountry(params::GetCountry {
+            version: params_version @ None,
+        })
+        | Self::Buy(params::Buy {
+            version: params_version @ None,
+            ..
+        }) = &mut self
+        {
+            *params_version = Some(version.clone());
+        }
+
+        self
+    }
 }
 
 impl Display for ApiMethod {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Self::GetPrice(_) => "getprice",
-            Self::GetCount(_) => "getcount",
-            Self::GetCountry(_) => "getcountry",
-            Self::GetProxy(_) => "getproxy",
-            Self::SetType(_) => "settype",
-            Self::SetDescription(_) => "setdescr",
-            Self::Buy(_) => "buy",
-            Self::Prolong(_) => "prolong",
-            Self::Delete(_) => "delete",
-            Self::Check(_) => "check",
-            Self::IpAuth(_) => "ipauth",
+            Self::Raw(params) => params.method.fmt(f),
+            _ => self.name().fmt(f),
         }
-        .fmt(f)
     }
 }
 
@@ -63,7 +181,7 @@ mod tests {
     #[test]
     fn test_get_price_display() {
         let params = params::GetPrice {
-            count: 10,
+            count: crate::value_object::ProxyCount::new(10).unwrap(),
             period: crate::value_object::ProxyPeriod::new(30).unwrap(),
             version: Some(crate::value_object::ProxyVersion::Ipv6),
         };
@@ -100,6 +218,7 @@ mod tests {
             description: None,
             page: None,
             limit: None,
+            keyed_list: false,
         };
         let method = ApiMethod::GetProxy(params);
 
@@ -109,7 +228,8 @@ mod tests {
     #[test]
     fn test_set_type_display() {
         let params = params::SetType {
-            ids: vec![crate::value_object::ProxyId::new("id1")],
+            ids: crate::value_object::ProxyIds::new(vec![crate::value_object::ProxyId::new("id1")])
+                .unwrap(),
             r#type: crate::value_object::ProxyType::Http,
         };
         let method = ApiMethod::SetType(params);
@@ -121,8 +241,11 @@ mod tests {
     fn test_set_description_display() {
         let params = params::SetDescription {
             new: crate::value_object::ProxyDescription::new("test").unwrap(),
-            old: None,
-            ids: None,
+            target: crate::value_object::DescriptionTarget::new(
+                None,
+                Some(vec![crate::value_object::ProxyId::new("1")]),
+            )
+            .unwrap(),
         };
         let method = ApiMethod::SetDescription(params);
 
@@ -132,13 +255,14 @@ mod tests {
     #[test]
     fn test_buy_display() {
         let params = params::Buy {
-            count: 1,
+            count: crate::value_object::ProxyCount::new(1).unwrap(),
             period: crate::value_object::ProxyPeriod::new(30).unwrap(),
             country: crate::value_object::Country::new("us").unwrap(),
             version: None,
             r#type: None,
             description: None,
             auto_prolong: false,
+            keyed_list: false,
         };
         let method = ApiMethod::Buy(params);
 
@@ -150,6 +274,7 @@ mod tests {
         let params = params::Prolong {
             period: crate::value_object::ProxyPeriod::new(30).unwrap(),
             ids: vec![crate::value_object::ProxyId::new("id1")],
+            keyed_list: false,
         };
         let method = ApiMethod::Prolong(params);
 
@@ -159,8 +284,11 @@ mod tests {
     #[test]
     fn test_delete_display() {
         let params = params::Delete {
-            ids: None,
-            description: None,
+            target: crate::value_object::DeleteTarget::new(
+                Some(vec![crate::value_object::ProxyId::new("1")]),
+                None,
+            )
+            .unwrap(),
         };
         let method = ApiMethod::Delete(params);
 
@@ -169,10 +297,10 @@ mod tests {
 
     #[test]
     fn test_check_display() {
-        let params = params::Check {
-            ids: None,
-            proxy_string: None,
-        };
+        let params = params::Check::by_ids(
+            crate::value_object::ProxyIds::new(vec![crate::value_object::ProxyId::new("1")])
+                .unwrap(),
+        );
         let method = ApiMethod::Check(params);
 
         assert_eq!(method.to_string(), "check");
@@ -188,10 +316,43 @@ mod tests {
         assert_eq!(method.to_string(), "ipauth");
     }
 
+    #[cfg(any(feature = "async_client", feature = "sync_client"))]
+    #[test]
+    fn test_with_default_version_fills_unset_version() {
+        let method = ApiMethod::GetCountry(params::GetCountry { version: None })
+            .with_default_version(&crate::value_object::ProxyVersion::Ipv4);
+
+        assert!(matches!(
+            method,
+            ApiMethod::GetCountry(params::GetCountry {
+                version: Some(crate::value_object::ProxyVersion::Ipv4)
+            })
+        ));
+    }
+
+    #[cfg(any(feature = "async_client", feature = "sync_client"))]
+    #[test]
+    fn test_with_default_version_keeps_explicit_version() {
+        let method = ApiMethod::GetCount(params::GetCount {
+            country: crate::value_object::Country::new("us").unwrap(),
+            version: Some(crate::value_object::ProxyVersion::Ipv6),
+        })
+        .with_default_version(&crate::value_object::ProxyVersion::Ipv4);
+
+        assert!(matches!(
+            method,
+            ApiMethod::GetCount(params::GetCount {
+                version: Some(crate::value_object::ProxyVersion::Ipv6),
+                ..
+            })
+        ));
+    }
+
+    #[cfg(any(feature = "async_client", feature = "sync_client"))]
     #[test]
     fn test_get_params() {
         let params = params::GetPrice {
-            count: 10,
+            count: crate::value_object::ProxyCount::new(10).unwrap(),
             period: crate::value_object::ProxyPeriod::new(30).unwrap(),
             version: Some(crate::value_object::ProxyVersion::Ipv6),
         };
@@ -211,4 +372,87 @@ mod tests {
             _ => panic!("Expected GetPrice method"),
         }
     }
+
+    #[test]
+    fn test_method_name() {
+        assert_eq!(MethodName::SetDescription.as_str(), "setdescr");
+        assert_eq!(MethodName::IpAuth.to_string(), "ipauth");
+        assert_eq!(
+            ApiMethod::GetCountry(params::GetCountry { version: None }).name(),
+            params::GetCountry::METHOD_NAME
+        );
+    }
+
+    #[test]
+    fn test_is_idempotent() {
+        let ids = vec![crate::value_object::ProxyId::new("1")];
+        let period = crate::value_object::ProxyPeriod::new(30).unwrap();
+        let reads = [
+            ApiMethod::GetPrice(params::GetPrice {
+                count: crate::value_object::ProxyCount::new(1).unwrap(),
+                period: period.clone(),
+                version: None,
+            }),
+            ApiMethod::GetCount(params::GetCount {
+                country: crate::value_object::Country::new("us").unwrap(),
+                version: None,
+            }),
+            ApiMethod::GetCountry(params::GetCountry { version: None }),
+            ApiMethod::GetProxy(params::GetProxy::builder().build()),
+            ApiMethod::Check(params::Check::by_ids(
+                crate::value_object::ProxyIds::new(ids.clone()).unwrap(),
+            )),
+        ];
+        let writes = [
+            ApiMethod::SetType(params::SetType {
+                ids: crate::value_object::ProxyIds::new(ids.clone()).unwrap(),
+                r#type: crate::value_object::ProxyType::Http,
+            }),
+            ApiMethod::SetDescription(params::SetDescription {
+                new: crate::value_object::ProxyDescription::new("test").unwrap(),
+                target: crate::value_object::DescriptionTarget::new(None, Some(ids.clone()))
+                    .unwrap(),
+            }),
+            ApiMethod::Buy(params::Buy::new(
+                crate::value_object::ProxyCount::new(1).unwrap(),
+                period.clone(),
+                crate::value_object::Country::new("us").unwrap(),
+            )),
+            ApiMethod::Prolong(params::Prolong {
+                period,
+                ids: ids.clone(),
+                keyed_list: false,
+            }),
+            ApiMethod::Delete(params::Delete {
+                target: crate::value_object::DeleteTarget::new(Some(ids), None).unwrap(),
+            }),
+            ApiMethod::IpAuth(params::IpAuth {
+                ip: crate::value_object::IpsToConnect::Delete,
+            }),
+            ApiMethod::Raw(RawParams {
+                method: "getprice".to_string(),
+                params: Vec::new(),
+            }),
+        ];
+
+        for method in reads {
+            assert!(method.is_idempotent(), "{method} must be idempotent");
+        }
+        for method in writes {
+            assert!(!method.is_idempotent(), "{method} must not be idempotent");
+        }
+    }
+
+    #[cfg(any(feature = "async_client", feature = "sync_client"))]
+    #[test]
+    fn test_raw_display_and_params() {
+        let method = ApiMethod::Raw(RawParams {
+            method: "newthing".to_string(),
+            params: vec![("ids".to_string(), "1,2".to_string())],
+        });
+
+        assert_eq!(method.to_string(), "newthing");
+        assert_eq!(method.name(), MethodName::Raw);
+        assert_eq!(method.get_params().to_query_string(), "ids=1,2");
+    }
 }
@@ -1,3 +1,6 @@
+use serde::{Deserialize, Serialize};
+
+use crate::method::MethodName;
 #[allow(clippy::wildcard_imports)]
 use crate::value_object::*;
 
@@ -6,29 +9,100 @@ pub(crate) trait ApiParams {
     fn to_query_tuple(&self) -> Vec<(&str, Option<String>)>;
 
     fn to_query_string(&self) -> String {
-        self.to_query_tuple()
-            .into_iter()
-            .filter_map(|(key, maybe_value)| {
-                let value = maybe_value?;
+        join_query_tuple(self.to_query_tuple())
+    }
+
+    /// Same as [`Self::to_query_string`] with secrets masked, so it can be logged.
+    fn to_redacted_query_string(&self) -> String {
+        self.to_query_string()
+    }
+}
 
-                if value.is_empty() {
-                    return Some(key.to_string());
+fn join_query_tuple(query_tuple: Vec<(&str, Option<String>)>) -> String {
+    query_tuple
+        .into_iter()
+        .filter_map(|(key, maybe_value)| {
+            let value = maybe_value?;
+
+            if value.is_empty() {
+                return Some(key.to_string());
+            }
+
+            Some(format!("{key}={}", percent_encode_query(&value)))
+        })
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+/// Exposes the query strings of [`ApiParams`] on params without their own docs and examples.
+macro_rules! impl_query_string {
+    ($($name:ident),+ $(,)?) => {
+        $(
+            impl $name {
+                /// Query string sent to the API for these params, see [`GetPrice::query_string`].
+                #[must_use]
+                pub fn query_string(&self) -> String {
+                    self.to_query_string()
                 }
 
-                Some(format!("{key}={value}"))
-            })
-            .collect::<Vec<_>>()
-            .join("&")
-    }
+                /// Query string to log for these params, see [`Check::redacted_query_string`].
+                #[must_use]
+                pub fn redacted_query_string(&self) -> String {
+                    self.to_redacted_query_string()
+                }
+            }
+        )+
+    };
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+impl_query_string!(
+    GetCount,
+    GetCountry,
+    GetProxy,
+    SetType,
+    SetDescription,
+    Buy,
+    Prolong,
+    Delete,
+    IpAuth,
+);
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct GetPrice {
-    pub count: usize,
+    pub count: ProxyCount,
     pub period: ProxyPeriod,
     pub version: Option<ProxyVersion>,
 }
 
+impl GetPrice {
+    /// Name of the API method these params are sent to.
+    pub const METHOD_NAME: MethodName = MethodName::GetPrice;
+
+    /// Query string sent to the API for these params, e.g. to check them in your own tests.
+    ///
+    /// ```
+    /// use proxy6::{ProxyCount, ProxyPeriod, ProxyVersion, params::GetPrice};
+    ///
+    /// let params = GetPrice {
+    ///     count: ProxyCount::new(100)?,
+    ///     period: ProxyPeriod::new(30)?,
+    ///     version: Some(ProxyVersion::Ipv6),
+    /// };
+    /// assert_eq!(params.query_string(), "count=100&period=30&version=6");
+    /// # Ok::<(), proxy6::BuildError>(())
+    /// ```
+    #[must_use]
+    pub fn query_string(&self) -> String {
+        self.to_query_string()
+    }
+
+    /// Query string to log for these params, see [`Check::redacted_query_string`].
+    #[must_use]
+    pub fn redacted_query_string(&self) -> String {
+        self.to_redacted_query_string()
+    }
+}
+
 impl ApiParams for GetPrice {
     fn to_query_tuple(&self) -> Vec<(&str, Option<String>)> {
         vec![
@@ -39,12 +113,17 @@ impl ApiParams for GetPrice {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct GetCount {
     pub country: Country,
     pub version: Option<ProxyVersion>,
 }
 
+impl GetCount {
+    /// Name of the API method these params are sent to.
+    pub const METHOD_NAME: MethodName = MethodName::GetCount;
+}
+
 impl ApiParams for GetCount {
     fn to_query_tuple(&self) -> Vec<(&str, Option<String>)> {
         vec![
@@ -54,23 +133,121 @@ impl ApiParams for GetCount {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct GetCountry {
     pub version: Option<ProxyVersion>,
 }
 
+impl GetCountry {
+    /// Name of the API method these params are sent to.
+    pub const METHOD_NAME: MethodName = MethodName::GetCountry;
+}
+
 impl ApiParams for GetCountry {
     fn to_query_tuple(&self) -> Vec<(&str, Option<String>)> {
         vec![("version", self.version.as_ref().map(ToString::to_string))]
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct GetProxy {
     pub state: Option<ProxyStatus>,
     pub description: Option<ProxyDescription>,
     pub page: Option<usize>,
     pub limit: Option<PageLimit>,
+    /// Whether the API returns the list keyed by proxy id instead of a plain array, i.e. `nokey` is not sent.
+    ///
+    /// Both shapes are parsed into the same response, defaults to a plain array.
+    #[serde(default)]
+    pub keyed_list: bool,
+}
+
+impl GetProxy {
+    /// Name of the API method these params are sent to.
+    pub const METHOD_NAME: MethodName = MethodName::GetProxy;
+
+    #[must_use]
+    pub fn builder() -> GetProxyBuilder {
+        GetProxyBuilder::default()
+    }
+
+    /// Params of the first page to collect all proxies from, page and limit default to server ones.
+    #[cfg(any(feature = "async_client", feature = "sync_client"))]
+    pub(crate) fn first_page(self) -> Self {
+        Self {
+            page: Some(self.page.unwrap_or(1)),
+            limit: Some(self.limit.unwrap_or_default()),
+            ..self
+        }
+    }
+
+    /// Params of the page following this one, `None` when the received page is the last one.
+    #[cfg(any(feature = "async_client", feature = "sync_client"))]
+    pub(crate) fn next_page(&self, received: usize, remaining: usize) -> Option<Self> {
+        let limit = self.limit.clone().unwrap_or_default();
+        if received < usize::from(limit.as_u16()) || remaining == 0 {
+            return None;
+        }
+
+        Some(Self {
+            page: Some(self.page.unwrap_or(1) + 1),
+            ..self.clone()
+        })
+    }
+}
+
+/// Builder of [`GetProxy`], every param is optional.
+#[derive(Debug, Clone, Default)]
+pub struct GetProxyBuilder {
+    state: Option<ProxyStatus>,
+    description: Option<ProxyDescription>,
+    page: Option<usize>,
+    limit: Option<PageLimit>,
+    keyed_list: bool,
+}
+
+impl GetProxyBuilder {
+    #[must_use]
+    pub const fn state(mut self, state: ProxyStatus) -> Self {
+        self.state = Some(state);
+        self
+    }
+
+    #[must_use]
+    pub fn description(mut self, description: ProxyDescription) -> Self {
+        self.description = Some(description);
+        self
+    }
+
+    #[must_use]
+    pub const fn page(mut self, page: usize) -> Self {
+        self.page = Some(page);
+        self
+    }
+
+    #[must_use]
+    pub const fn limit(mut self, limit: PageLimit) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// See [`GetProxy::keyed_list`].
+    #[must_use]
+    pub const fn keyed_list(mut self, keyed_list: bool) -> Self {
+        self.keyed_list = keyed_list;
+        self
+    }
+
+    #[must_use]
+    pub fn build(self) -> GetProxy {
+        GetProxy {
+            state: self.state,
+            description: self.description,
+            page: self.page,
+            limit: self.limit,
+            keyed_list: self.keyed_list,
+        }
+    }
 }
 
 impl ApiParams for GetProxy {
@@ -80,17 +257,22 @@ impl ApiParams for GetProxy {
             ("descr", self.description.as_ref().map(ToString::to_string)),
             ("page", self.page.map(|page| page.to_string())),
             ("limit", self.limit.as_ref().map(ToString::to_string)),
-            ("nokey", Some(String::new())),
+            ("nokey", (!self.keyed_list).then(String::new)),
         ]
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct SetType {
-    pub ids: Vec<ProxyId>,
+    pub ids: ProxyIds,
     pub r#type: ProxyType,
 }
 
+impl SetType {
+    /// Name of the API method these params are sent to.
+    pub const METHOD_NAME: MethodName = MethodName::SetType;
+}
+
 impl ApiParams for SetType {
     fn to_query_tuple(&self) -> Vec<(&str, Option<String>)> {
         vec![
@@ -98,6 +280,7 @@ impl ApiParams for SetType {
                 "ids",
                 Some(
                     self.ids
+                        .as_slice()
                         .iter()
                         .map(ToString::to_string)
                         .collect::<Vec<_>>()
@@ -109,21 +292,25 @@ impl ApiParams for SetType {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct SetDescription {
     pub new: ProxyDescription,
-    pub old: Option<ProxyDescription>, // old or ids is required
-    pub ids: Option<Vec<ProxyId>>,
+    pub target: DescriptionTarget,
+}
+
+impl SetDescription {
+    /// Name of the API method these params are sent to.
+    pub const METHOD_NAME: MethodName = MethodName::SetDescription;
 }
 
 impl ApiParams for SetDescription {
     fn to_query_tuple(&self) -> Vec<(&str, Option<String>)> {
         vec![
             ("new", Some(self.new.to_string())),
-            ("old", self.old.as_ref().map(ToString::to_string)),
+            ("old", self.target.old().map(ToString::to_string)),
             (
                 "ids",
-                self.ids.as_ref().map(|ids| {
+                self.target.ids().map(|ids| {
                     ids.iter()
                         .map(ToString::to_string)
                         .collect::<Vec<_>>()
@@ -134,15 +321,86 @@ impl ApiParams for SetDescription {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct Buy {
-    pub count: usize,
+    pub count: ProxyCount,
     pub period: ProxyPeriod,
     pub country: Country,
     pub version: Option<ProxyVersion>,
     pub r#type: Option<ProxyType>,
     pub description: Option<ProxyDescription>,
+    #[serde(default)]
     pub auto_prolong: bool,
+    /// See [`GetProxy::keyed_list`].
+    #[serde(default)]
+    pub keyed_list: bool,
+}
+
+impl Buy {
+    /// Name of the API method these params are sent to.
+    pub const METHOD_NAME: MethodName = MethodName::Buy;
+
+    /// Params to buy `count` proxies for `period` days without optional settings.
+    #[must_use]
+    pub const fn new(count: ProxyCount, period: ProxyPeriod, country: Country) -> Self {
+        Self {
+            count,
+            period,
+            country,
+            version: None,
+            r#type: None,
+            description: None,
+            auto_prolong: false,
+            keyed_list: false,
+        }
+    }
+
+    #[must_use]
+    pub const fn version(mut self, version: ProxyVersion) -> Self {
+        self.version = Some(version);
+        self
+    }
+
+    #[must_use]
+    pub fn proxy_type(mut self, r#type: ProxyType) -> Self {
+        self.r#type = Some(r#type);
+        self
+    }
+
+    #[must_use]
+    pub fn description(mut self, description: ProxyDescription) -> Self {
+        self.description = Some(description);
+        self
+    }
+
+    #[must_use]
+    pub const fn auto_prolong(mut self, auto_prolong: bool) -> Self {
+        self.auto_prolong = auto_prolong;
+        self
+    }
+
+    /// See [`GetProxy::keyed_list`].
+    #[must_use]
+    pub const fn keyed_list(mut self, keyed_list: bool) -> Self {
+        self.keyed_list = keyed_list;
+        self
+    }
+
+    /// Splits the order into orders of at most `chunk_size` proxies.
+    #[cfg(any(feature = "async_client", feature = "sync_client"))]
+    pub(crate) fn chunks(self, chunk_size: ProxyCount) -> Vec<Self> {
+        let total = self.count.as_usize();
+        let chunk_size = chunk_size.as_usize();
+
+        (0..total)
+            .step_by(chunk_size)
+            .filter_map(|offset| ProxyCount::new(chunk_size.min(total - offset)).ok())
+            .map(|count| Self {
+                count,
+                ..self.clone()
+            })
+            .collect()
+    }
 }
 
 impl ApiParams for Buy {
@@ -162,15 +420,36 @@ impl ApiParams for Buy {
                     None
                 },
             ),
-            ("nokey", Some(String::new())),
+            ("nokey", (!self.keyed_list).then(String::new)),
         ]
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct Prolong {
     pub period: ProxyPeriod,
     pub ids: Vec<ProxyId>,
+    /// See [`GetProxy::keyed_list`].
+    #[serde(default)]
+    pub keyed_list: bool,
+}
+
+impl Prolong {
+    /// Name of the API method these params are sent to.
+    pub const METHOD_NAME: MethodName = MethodName::Prolong;
+
+    /// Splits the ids into requests of at most `chunk_size` proxies.
+    #[cfg(any(feature = "async_client", feature = "sync_client"))]
+    pub(crate) fn chunks(self, chunk_size: ProxyCount) -> Vec<Self> {
+        self.ids
+            .chunks(chunk_size.as_usize())
+            .map(|ids| Self {
+                period: self.period.clone(),
+                ids: ids.to_vec(),
+                keyed_list: self.keyed_list,
+            })
+            .collect()
+    }
 }
 
 impl ApiParams for Prolong {
@@ -187,15 +466,19 @@ impl ApiParams for Prolong {
                         .join(","),
                 ),
             ),
-            ("nokey", Some(String::new())),
+            ("nokey", (!self.keyed_list).then(String::new)),
         ]
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct Delete {
-    pub ids: Option<Vec<ProxyId>>,
-    pub description: Option<ProxyDescription>, // ids or description is required
+    pub target: DeleteTarget,
+}
+
+impl Delete {
+    /// Name of the API method these params are sent to.
+    pub const METHOD_NAME: MethodName = MethodName::Delete;
 }
 
 impl ApiParams for Delete {
@@ -203,46 +486,109 @@ impl ApiParams for Delete {
         vec![
             (
                 "ids",
-                self.ids.as_ref().map(|ids| {
+                self.target.ids().map(|ids| {
                     ids.iter()
                         .map(ToString::to_string)
                         .collect::<Vec<_>>()
                         .join(",")
                 }),
             ),
-            ("descr", self.description.as_ref().map(ToString::to_string)),
+            ("descr", self.target.description().map(ToString::to_string)),
         ]
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct Check {
-    pub ids: Option<Vec<ProxyId>>,
-    pub proxy_string: Option<ProxyString>, // ids of proxy_string is required
+    pub target: CheckTarget,
+}
+
+impl Check {
+    #[must_use]
+    pub const fn by_ids(ids: ProxyIds) -> Self {
+        Self {
+            target: CheckTarget::Ids(ids),
+        }
+    }
+
+    #[must_use]
+    pub const fn by_proxy_string(proxy_string: ProxyString) -> Self {
+        Self {
+            target: CheckTarget::ProxyString(proxy_string),
+        }
+    }
+}
+
+impl Check {
+    /// Name of the API method these params are sent to.
+    pub const METHOD_NAME: MethodName = MethodName::Check;
+
+    /// Query string sent to the API for these params, see [`GetPrice::query_string`].
+    #[must_use]
+    pub fn query_string(&self) -> String {
+        self.to_query_string()
+    }
+
+    /// Same as [`Self::query_string`], but the password of a proxy string is masked, so it can be logged.
+    ///
+    /// ```
+    /// use proxy6::{ProxyString, params::Check};
+    ///
+    /// let params = Check::by_proxy_string(ProxyString::new("127.0.0.1:8080:user:secret")?);
+    /// assert_eq!(params.redacted_query_string(), "proxy=127.0.0.1:8080:user:***");
+    /// # Ok::<(), proxy6::BuildError>(())
+    /// ```
+    #[must_use]
+    pub fn redacted_query_string(&self) -> String {
+        self.to_redacted_query_string()
+    }
 }
 
 impl ApiParams for Check {
     fn to_query_tuple(&self) -> Vec<(&str, Option<String>)> {
-        vec![
-            (
+        match &self.target {
+            CheckTarget::Ids(ids) => vec![(
                 "ids",
-                self.ids.as_ref().map(|ids| {
-                    ids.iter()
+                Some(
+                    ids.as_slice()
+                        .iter()
                         .map(ToString::to_string)
                         .collect::<Vec<_>>()
-                        .join(",")
-                }),
-            ),
-            ("proxy", self.proxy_string.as_ref().map(ToString::to_string)),
-        ]
+                        .join(","),
+                ),
+            )],
+            CheckTarget::ProxyString(proxy_string) => {
+                vec![("proxy", Some(proxy_string.to_string()))]
+            }
+        }
+    }
+
+    fn to_redacted_query_string(&self) -> String {
+        match &self.target {
+            CheckTarget::Ids(_) => self.to_query_string(),
+            CheckTarget::ProxyString(proxy_string) => {
+                // the mask is appended after encoding, so it stays readable
+                let without_pass = proxy_string
+                    .as_str()
+                    .rsplit_once(':')
+                    .map_or("", |(without_pass, _)| without_pass);
+
+                format!("proxy={}:***", percent_encode_query(without_pass))
+            }
+        }
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct IpAuth {
     pub ip: IpsToConnect,
 }
 
+impl IpAuth {
+    /// Name of the API method these params are sent to.
+    pub const METHOD_NAME: MethodName = MethodName::IpAuth;
+}
+
 impl ApiParams for IpAuth {
     fn to_query_tuple(&self) -> Vec<(&str, Option<String>)> {
         vec![("ip", Some(self.ip.to_string()))]
@@ -258,7 +604,7 @@ mod tests {
     #[test]
     fn test_convert_full_get_price_to_query_string() {
         let request = GetPrice {
-            count: 10,
+            count: ProxyCount::new(10).unwrap(),
             period: ProxyPeriod::new(30).unwrap(),
             version: Some(ProxyVersion::Ipv6),
         };
@@ -269,7 +615,7 @@ mod tests {
     #[test]
     fn test_convert_minimal_get_price_to_query_string() {
         let request = GetPrice {
-            count: 10,
+            count: ProxyCount::new(10).unwrap(),
             period: ProxyPeriod::new(30).unwrap(),
             version: None,
         };
@@ -287,6 +633,66 @@ mod tests {
         assert_eq!(request.to_query_string(), "country=uk");
     }
 
+    #[test]
+    fn test_convert_get_price_with_every_version_to_query_string() {
+        for (version, expected) in [
+            (ProxyVersion::Ipv4, "count=10&period=30&version=4"),
+            (ProxyVersion::Ipv4Shared, "count=10&period=30&version=3"),
+            (ProxyVersion::Ipv6, "count=10&period=30&version=6"),
+        ] {
+            let request = GetPrice {
+                count: ProxyCount::new(10).unwrap(),
+                period: ProxyPeriod::new(30).unwrap(),
+                version: Some(version),
+            };
+
+            assert_eq!(request.to_query_string(), expected);
+        }
+    }
+
+    #[test]
+    fn test_convert_get_count_with_every_version_to_query_string() {
+        for (version, expected) in [
+            (ProxyVersion::Ipv4, "country=uk&version=4"),
+            (ProxyVersion::Ipv4Shared, "country=uk&version=3"),
+            (ProxyVersion::Ipv6, "country=uk&version=6"),
+        ] {
+            let request = GetCount {
+                country: Country::new("uk").unwrap(),
+                version: Some(version),
+            };
+
+            assert_eq!(request.to_query_string(), expected);
+        }
+    }
+
+    #[test]
+    fn test_convert_buy_with_every_version_to_query_string() {
+        for (version, expected) in [
+            (
+                ProxyVersion::Ipv4,
+                "count=1&period=7&country=us&version=4&nokey",
+            ),
+            (
+                ProxyVersion::Ipv4Shared,
+                "count=1&period=7&country=us&version=3&nokey",
+            ),
+            (
+                ProxyVersion::Ipv6,
+                "count=1&period=7&country=us&version=6&nokey",
+            ),
+        ] {
+            let request = Buy::new(
+                ProxyCount::new(1).unwrap(),
+                ProxyPeriod::new(7).unwrap(),
+                Country::new("us").unwrap(),
+            )
+            .version(version);
+
+            assert_eq!(request.to_query_string(), expected);
+        }
+    }
+
     #[test]
     fn test_convert_full_get_country_to_query_string() {
         let request = GetCountry {
@@ -310,6 +716,7 @@ mod tests {
             description: Some(ProxyDescription::new("test_description").unwrap()),
             page: Some(3),
             limit: Some(PageLimit::new(10).unwrap()),
+            keyed_list: false,
         };
 
         assert_eq!(
@@ -325,15 +732,128 @@ mod tests {
             description: None,
             page: None,
             limit: None,
+            keyed_list: false,
         };
 
         assert_eq!(request.to_query_string(), "nokey");
     }
 
+    #[test]
+    fn test_get_proxy_builder_defaults() {
+        assert_eq!(
+            GetProxy::builder().build(),
+            GetProxy {
+                state: None,
+                description: None,
+                page: None,
+                limit: None,
+                keyed_list: false,
+            }
+        );
+    }
+
+    #[test]
+    fn test_get_proxy_builder() {
+        let request = GetProxy::builder()
+            .state(ProxyStatus::Active)
+            .description(ProxyDescription::new("test").unwrap())
+            .page(2)
+            .limit(PageLimit::new(10).unwrap())
+            .build();
+
+        assert_eq!(
+            request,
+            GetProxy {
+                state: Some(ProxyStatus::Active),
+                description: Some(ProxyDescription::new("test").unwrap()),
+                page: Some(2),
+                limit: Some(PageLimit::new(10).unwrap()),
+                keyed_list: false,
+            }
+        );
+    }
+
+    #[cfg(any(feature = "async_client", feature = "sync_client"))]
+    #[test]
+    fn test_get_proxy_first_page_defaults() {
+        let request = GetProxy {
+            state: None,
+            description: None,
+            page: None,
+            limit: None,
+            keyed_list: false,
+        }
+        .first_page();
+
+        assert_eq!(request.page, Some(1));
+        assert_eq!(request.limit, Some(PageLimit::default()));
+    }
+
+    #[cfg(any(feature = "async_client", feature = "sync_client"))]
+    #[test]
+    fn test_get_proxy_first_page_keeps_values() {
+        let request = GetProxy {
+            state: None,
+            description: None,
+            page: Some(3),
+            limit: Some(PageLimit::new(10).unwrap()),
+            keyed_list: false,
+        }
+        .first_page();
+
+        assert_eq!(request.page, Some(3));
+        assert_eq!(request.limit, Some(PageLimit::new(10).unwrap()));
+    }
+
+    #[cfg(any(feature = "async_client", feature = "sync_client"))]
+    #[test]
+    fn test_get_proxy_next_page() {
+        let request = GetProxy {
+            state: Some(ProxyStatus::Active),
+            description: None,
+            page: Some(1),
+            limit: Some(PageLimit::new(10).unwrap()),
+            keyed_list: false,
+        };
+
+        let next = request.next_page(10, 15).unwrap();
+        assert_eq!(next.page, Some(2));
+        assert_eq!(next.state, Some(ProxyStatus::Active));
+        assert_eq!(next.limit, request.limit);
+    }
+
+    #[cfg(any(feature = "async_client", feature = "sync_client"))]
+    #[test]
+    fn test_get_proxy_next_page_stops_on_short_page() {
+        let request = GetProxy {
+            state: None,
+            description: None,
+            page: Some(3),
+            limit: Some(PageLimit::new(10).unwrap()),
+            keyed_list: false,
+        };
+
+        assert_eq!(request.next_page(5, 5), None);
+    }
+
+    #[cfg(any(feature = "async_client", feature = "sync_client"))]
+    #[test]
+    fn test_get_proxy_next_page_stops_when_all_collected() {
+        let request = GetProxy {
+            state: None,
+            description: None,
+            page: Some(2),
+            limit: Some(PageLimit::new(10).unwrap()),
+            keyed_list: false,
+        };
+
+        assert_eq!(request.next_page(10, 0), None);
+    }
+
     #[test]
     fn test_convert_full_set_type_to_query_string() {
         let request = SetType {
-            ids: vec![ProxyId::new("id1"), ProxyId::new("id2")],
+            ids: ProxyIds::new(vec![ProxyId::new("id1"), ProxyId::new("id2")]).unwrap(),
             r#type: ProxyType::Socks5,
         };
 
@@ -344,8 +864,11 @@ mod tests {
     fn test_convert_full_set_description_to_query_string() {
         let request = SetDescription {
             new: ProxyDescription::new("new_proxy_description").unwrap(),
-            old: Some(ProxyDescription::new("old_proxy_description").unwrap()),
-            ids: Some(vec![ProxyId::new("id1"), ProxyId::new("id2")]),
+            target: DescriptionTarget::new(
+                Some(ProxyDescription::new("old_proxy_description").unwrap()),
+                Some(vec![ProxyId::new("id1"), ProxyId::new("id2")]),
+            )
+            .unwrap(),
         };
 
         assert_eq!(
@@ -355,26 +878,55 @@ mod tests {
     }
 
     #[test]
-    fn test_convert_minimal_set_description_to_query_string() {
+    fn test_convert_set_description_by_old_to_query_string() {
+        let request = SetDescription {
+            new: ProxyDescription::new("new_proxy_description").unwrap(),
+            target: DescriptionTarget::Old(ProxyDescription::new("old_proxy_description").unwrap()),
+        };
+
+        assert_eq!(
+            request.to_query_string(),
+            "new=new_proxy_description&old=old_proxy_description"
+        );
+    }
+
+    #[test]
+    fn test_convert_set_description_by_ids_to_query_string() {
         let request = SetDescription {
             new: ProxyDescription::new("new_proxy_description").unwrap(),
-            old: None,
-            ids: None,
+            target: DescriptionTarget::new(None, Some(vec![ProxyId::new("id1")])).unwrap(),
+        };
+
+        assert_eq!(
+            request.to_query_string(),
+            "new=new_proxy_description&ids=id1"
+        );
+    }
+
+    #[test]
+    fn test_convert_set_description_with_special_characters_to_query_string() {
+        let request = SetDescription {
+            new: ProxyDescription::new("new & shiny #1").unwrap(),
+            target: DescriptionTarget::Old(ProxyDescription::new("a=b c").unwrap()),
         };
 
-        assert_eq!(request.to_query_string(), "new=new_proxy_description");
+        assert_eq!(
+            request.to_query_string(),
+            "new=new%20%26%20shiny%20%231&old=a%3Db%20c"
+        );
     }
 
     #[test]
     fn test_convert_full_buy_to_query_string() {
         let request = Buy {
-            count: 100,
+            count: ProxyCount::new(100).unwrap(),
             period: ProxyPeriod::new(30).unwrap(),
             country: Country::new("us").unwrap(),
             version: Some(ProxyVersion::Ipv6),
             r#type: Some(ProxyType::Http),
             description: Some(ProxyDescription::new("new_proxy_description").unwrap()),
             auto_prolong: true,
+            keyed_list: false,
         };
 
         assert_eq!(
@@ -386,13 +938,14 @@ mod tests {
     #[test]
     fn test_convert_minimal_buy_to_query_string() {
         let request = Buy {
-            count: 100,
+            count: ProxyCount::new(100).unwrap(),
             period: ProxyPeriod::new(30).unwrap(),
             country: Country::new("us").unwrap(),
             version: None,
             r#type: None,
             description: None,
             auto_prolong: false,
+            keyed_list: false,
         };
 
         assert_eq!(
@@ -401,21 +954,141 @@ mod tests {
         );
     }
 
+    fn buy(count: usize) -> Buy {
+        Buy {
+            count: ProxyCount::new(count).unwrap(),
+            period: ProxyPeriod::new(30).unwrap(),
+            country: Country::new("us").unwrap(),
+            version: None,
+            r#type: None,
+            description: None,
+            auto_prolong: false,
+            keyed_list: false,
+        }
+    }
+
+    #[test]
+    fn test_buy_new_has_no_optional_settings() {
+        assert_eq!(
+            Buy::new(
+                ProxyCount::new(25).unwrap(),
+                ProxyPeriod::new(30).unwrap(),
+                Country::new("us").unwrap(),
+            ),
+            buy(25)
+        );
+    }
+
+    #[test]
+    fn test_buy_setters() {
+        let request = Buy::new(
+            ProxyCount::new(100).unwrap(),
+            ProxyPeriod::new(30).unwrap(),
+            Country::new("us").unwrap(),
+        )
+        .version(ProxyVersion::Ipv6)
+        .proxy_type(ProxyType::Http)
+        .description(ProxyDescription::new("new_proxy_description").unwrap())
+        .auto_prolong(true);
+
+        assert_eq!(
+            request.to_query_string(),
+            "count=100&period=30&country=us&version=6&type=http&descr=new_proxy_description&auto_prolong&nokey"
+        );
+    }
+
+    #[cfg(any(feature = "async_client", feature = "sync_client"))]
+    #[test]
+    fn test_buy_chunks() {
+        let counts = buy(25)
+            .chunks(ProxyCount::new(10).unwrap())
+            .iter()
+            .map(|chunk| chunk.count.as_usize())
+            .collect::<Vec<_>>();
+
+        assert_eq!(counts, [10, 10, 5]);
+    }
+
+    #[cfg(any(feature = "async_client", feature = "sync_client"))]
+    #[test]
+    fn test_buy_chunks_exact_and_smaller_than_chunk() {
+        let chunk_size = ProxyCount::new(10).unwrap();
+
+        assert_eq!(buy(20).chunks(chunk_size).len(), 2);
+        assert_eq!(buy(3).chunks(chunk_size), [buy(3)]);
+    }
+
+    #[cfg(any(feature = "async_client", feature = "sync_client"))]
+    #[test]
+    fn test_prolong_chunks() {
+        let request = Prolong {
+            period: ProxyPeriod::new(30).unwrap(),
+            ids: (1..=7).map(|id| ProxyId::new(id.to_string())).collect(),
+            keyed_list: false,
+        };
+
+        let chunks = request.chunks(ProxyCount::new(3).unwrap());
+
+        assert_eq!(chunks.len(), 3);
+        assert_eq!(
+            chunks
+                .iter()
+                .map(|chunk| chunk.ids.len())
+                .collect::<Vec<_>>(),
+            [3, 3, 1]
+        );
+        assert_eq!(chunks[2].ids, [ProxyId::new("7")]);
+        assert_eq!(chunks[2].period, ProxyPeriod::new(30).unwrap());
+    }
+
     #[test]
     fn test_convert_full_prolong_to_query_string() {
         let request = Prolong {
             period: ProxyPeriod::new(30).unwrap(),
             ids: vec![ProxyId::new("id1"), ProxyId::new("id2")],
+            keyed_list: false,
         };
 
         assert_eq!(request.to_query_string(), "period=30&ids=id1,id2&nokey");
     }
 
+    #[cfg(any(feature = "async_client", feature = "sync_client"))]
+    #[test]
+    fn test_keyed_list_omits_nokey() {
+        let prolong = Prolong {
+            period: ProxyPeriod::new(30).unwrap(),
+            ids: vec![ProxyId::new("id1")],
+            keyed_list: true,
+        };
+        assert_eq!(prolong.to_query_string(), "period=30&ids=id1");
+
+        let get_proxy = GetProxy::builder().keyed_list(true).build();
+        assert_eq!(get_proxy.to_query_string(), "");
+        assert_eq!(GetProxy::builder().build().to_query_string(), "nokey");
+
+        let buy = buy(1).keyed_list(true);
+        assert_eq!(buy.to_query_string(), "count=1&period=30&country=us");
+        assert!(
+            !buy.chunks(ProxyCount::new(1).unwrap())[0]
+                .to_query_string()
+                .contains("nokey")
+        );
+    }
+
+    #[test]
+    fn test_keyed_list_defaults_to_false_when_deserializing() {
+        let request: Prolong = serde_json::from_str(r#"{"period":30,"ids":["id1"]}"#).unwrap();
+        assert!(!request.keyed_list);
+    }
+
     #[test]
     fn test_convert_full_delete_to_query_string() {
         let request = Delete {
-            ids: Some(vec![ProxyId::new("id1"), ProxyId::new("id2")]),
-            description: Some(ProxyDescription::new("new_proxy_description").unwrap()),
+            target: DeleteTarget::new(
+                Some(vec![ProxyId::new("id1"), ProxyId::new("id2")]),
+                Some(ProxyDescription::new("new_proxy_description").unwrap()),
+            )
+            .unwrap(),
         };
 
         assert_eq!(
@@ -425,36 +1098,50 @@ mod tests {
     }
 
     #[test]
-    fn test_convert_minimal_delete_to_query_string() {
+    fn test_convert_delete_by_ids_to_query_string() {
         let request = Delete {
-            ids: None,
-            description: None,
+            target: DeleteTarget::new(Some(vec![ProxyId::new("id1")]), None).unwrap(),
         };
 
-        assert_eq!(request.to_query_string(), "");
+        assert_eq!(request.to_query_string(), "ids=id1");
     }
 
     #[test]
-    fn test_convert_full_check_to_query_string() {
-        let request = Check {
-            ids: Some(vec![ProxyId::new("id1"), ProxyId::new("id2")]),
-            proxy_string: Some(ProxyString::new("127.0.0.1:8080:user:pass").unwrap()),
+    fn test_convert_delete_by_description_to_query_string() {
+        let request = Delete {
+            target: DeleteTarget::Description(
+                ProxyDescription::new("new_proxy_description").unwrap(),
+            ),
         };
 
-        assert_eq!(
-            request.to_query_string(),
-            "ids=id1,id2&proxy=127.0.0.1:8080:user:pass"
-        );
+        assert_eq!(request.to_query_string(), "descr=new_proxy_description");
     }
 
     #[test]
-    fn test_convert_minimal_check_to_query_string() {
-        let request = Check {
-            ids: None,
-            proxy_string: None,
-        };
+    fn test_convert_check_by_ids_to_query_string() {
+        let request =
+            Check::by_ids(ProxyIds::new(vec![ProxyId::new("id1"), ProxyId::new("id2")]).unwrap());
 
-        assert_eq!(request.to_query_string(), "");
+        assert_eq!(request.to_query_string(), "ids=id1,id2");
+    }
+
+    #[test]
+    fn test_convert_check_by_proxy_string_to_query_string() {
+        let request = Check::by_proxy_string(ProxyString::new("127.0.0.1:8080:user:pass").unwrap());
+
+        assert_eq!(request.to_query_string(), "proxy=127.0.0.1:8080:user:pass");
+    }
+
+    #[test]
+    fn test_redacted_query_string_masks_proxy_password() {
+        let request = Check::by_proxy_string(ProxyString::new("::1:8080:user:p@ssword").unwrap());
+
+        assert_eq!(request.redacted_query_string(), "proxy=::1:8080:user:***");
+        assert!(!request.redacted_query_string().contains("p@ss"));
+        assert_eq!(
+            Check::by_ids(ProxyIds::new(vec![ProxyId::new("1")]).unwrap()).redacted_query_string(),
+            "ids=1"
+        );
     }
 
     #[test]
@@ -469,12 +1156,85 @@ mod tests {
     #[test]
     fn test_convert_select_ip_auth_to_query_string() {
         let request = IpAuth {
-            ip: IpsToConnect::Connect(vec![
+            ip: IpsToConnect::connect(vec![
                 "127.0.0.1".parse().unwrap(),
                 "127.0.0.2".parse().unwrap(),
-            ]),
+            ])
+            .unwrap(),
         };
 
         assert_eq!(request.to_query_string(), "ip=127.0.0.1,127.0.0.2");
     }
+
+    #[test]
+    fn test_deserialize_buy() {
+        let request: Buy = serde_json::from_str(
+            r#"{"count":2,"period":"30","country":"RU","version":"4","type":"socks","description":"bot"}"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            request,
+            Buy::new(
+                ProxyCount::new(2).unwrap(),
+                ProxyPeriod::new(30).unwrap(),
+                Country::new("ru").unwrap()
+            )
+            .version(ProxyVersion::Ipv4)
+            .proxy_type(ProxyType::Socks5)
+            .description(ProxyDescription::new("bot").unwrap())
+        );
+    }
+
+    #[test]
+    fn test_deserialize_buy_unknown_type_and_version_error() {
+        assert!(
+            serde_json::from_str::<Buy>(r#"{"count":2,"period":30,"country":"ru","type":"ftp"}"#)
+                .is_err()
+        );
+        assert!(
+            serde_json::from_str::<Buy>(r#"{"count":2,"period":30,"country":"ru","version":"5"}"#)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_deserialize_empty_selectors_error() {
+        assert!(serde_json::from_str::<SetType>(r#"{"ids":[],"type":"http"}"#).is_err());
+        assert!(serde_json::from_str::<Delete>(r#"{"target":{"Ids":[]}}"#).is_err());
+        assert!(serde_json::from_str::<Check>(r#"{"target":{"Ids":[]}}"#).is_err());
+        assert!(
+            serde_json::from_str::<SetDescription>(r#"{"new":"x","target":{"Ids":[]}}"#).is_err()
+        );
+        assert!(serde_json::from_str::<IpAuth>(r#"{"ip":{"Connect":[]}}"#).is_err());
+    }
+
+    #[test]
+    fn test_deserialize_ip_auth_deduplicates_ips() {
+        let request: IpAuth =
+            serde_json::from_str(r#"{"ip":{"Connect":["1.1.1.1","1.1.1.1","::ffff:1.1.1.1"]}}"#)
+                .unwrap();
+
+        assert_eq!(request.to_query_string(), "ip=1.1.1.1");
+    }
+
+    #[test]
+    fn test_deserialize_buy_validates_values() {
+        let invalid_country =
+            serde_json::from_str::<Buy>(r#"{"count":2,"period":30,"country":"russia"}"#)
+                .unwrap_err();
+        let invalid_period =
+            serde_json::from_str::<Buy>(r#"{"count":2,"period":0,"country":"ru"}"#).unwrap_err();
+
+        assert!(
+            invalid_country
+                .to_string()
+                .contains(&BuildError::CountryMustBeIso2.to_string())
+        );
+        assert!(
+            invalid_period
+                .to_string()
+                .contains(&BuildError::ProxyPeriodTooLow.to_string())
+        );
+    }
 }
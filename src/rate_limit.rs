@@ -0,0 +1,99 @@
+use std::{
+    collections::VecDeque,
+    sync::{Mutex, PoisonError},
+    time::Duration,
+};
+
+use crate::time::Instant;
+
+/// Sliding window limiter allowing at most `requests` sends during any `per` interval.
+///
+/// Callers only hold the lock while reserving a slot, so concurrent requests wait in parallel.
+#[derive(Debug)]
+pub struct RateLimiter {
+    requests: usize,
+    per: Duration,
+    scheduled: Mutex<VecDeque<Instant>>,
+}
+
+impl RateLimiter {
+    pub fn new(requests: u32, per: Duration) -> Self {
+        let requests = usize::try_from(requests).unwrap_or(usize::MAX);
+
+        Self {
+            requests,
+            per,
+            scheduled: Mutex::new(VecDeque::with_capacity(requests)),
+        }
+    }
+
+    /// Reserves a slot for the next request and returns how long to wait before sending it.
+    pub fn reserve(&self) -> Duration {
+        let now = Instant::now();
+        self.reserve_at(now).saturating_duration_since(now)
+    }
+
+    fn reserve_at(&self, now: Instant) -> Instant {
+        let mut scheduled = self
+            .scheduled
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+
+        let slot = if scheduled.len() < self.requests {
+            now
+        } else {
+            scheduled.pop_front().map_or(now, |oldest| {
+                oldest.checked_add(self.per).unwrap_or(oldest).max(now)
+            })
+        };
+
+        scheduled.push_back(slot);
+        slot
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_reserve_within_limit_does_not_wait() {
+        let limiter = RateLimiter::new(3, Duration::from_secs(1));
+        let now = Instant::now();
+
+        assert_eq!(limiter.reserve_at(now), now);
+        assert_eq!(limiter.reserve_at(now), now);
+        assert_eq!(limiter.reserve_at(now), now);
+    }
+
+    #[test]
+    fn test_reserve_over_limit_waits_for_window() {
+        let limiter = RateLimiter::new(2, Duration::from_secs(1));
+        let now = Instant::now();
+
+        assert_eq!(limiter.reserve_at(now), now);
+        assert_eq!(limiter.reserve_at(now), now);
+        assert_eq!(limiter.reserve_at(now), now + Duration::from_secs(1));
+        assert_eq!(limiter.reserve_at(now), now + Duration::from_secs(1));
+        assert_eq!(limiter.reserve_at(now), now + Duration::from_secs(2));
+    }
+
+    #[test]
+    fn test_reserve_after_idle_does_not_wait() {
+        let limiter = RateLimiter::new(1, Duration::from_secs(1));
+        let now = Instant::now();
+        let later = now + Duration::from_secs(5);
+
+        assert_eq!(limiter.reserve_at(now), now);
+        assert_eq!(limiter.reserve_at(later), later);
+    }
+
+    #[test]
+    fn test_reserve_returns_zero_delay_within_limit() {
+        let limiter = RateLimiter::new(1, Duration::from_secs(1));
+
+        assert_eq!(limiter.reserve(), Duration::ZERO);
+    }
+}
@@ -0,0 +1,22 @@
+//! Clock and timer of the clients, which differ on `wasm32` where `std::time::Instant` panics and there is no tokio timer.
+
+#[cfg(feature = "async_client")]
+use std::time::Duration;
+#[cfg(not(target_arch = "wasm32"))]
+pub use std::time::Instant;
+
+#[cfg(target_arch = "wasm32")]
+pub use web_time::Instant;
+
+/// Waits for `duration` without blocking the thread.
+#[cfg(all(feature = "async_client", not(target_arch = "wasm32")))]
+pub async fn sleep(duration: Duration) {
+    tokio::time::sleep(duration).await;
+}
+
+/// Waits for `duration` on a browser timer, capped at the longest timeout it accepts.
+#[cfg(all(feature = "async_client", target_arch = "wasm32"))]
+pub async fn sleep(duration: Duration) {
+    let longest = Duration::from_millis(u32::MAX.into());
+    gloo_timers::future::sleep(duration.min(longest)).await;
+}
@@ -0,0 +1,207 @@
+//! HTTP layer of the clients.
+//!
+//! Clients send requests with `reqwest` when the default `reqwest` feature is enabled, implement [`AsyncTransport`]
+//! or [`SyncTransport`] to use another HTTP stack or to return canned responses in tests.
+
+#[cfg(any(feature = "async_client", feature = "sync_client"))]
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex, PoisonError},
+};
+#[cfg(feature = "async_client")]
+use std::{future::Future, pin::Pin};
+
+#[cfg(any(feature = "async_client", feature = "sync_client"))]
+use crate::error::ApiError;
+#[cfg(all(
+    feature = "reqwest",
+    any(feature = "async_client", feature = "sync_client")
+))]
+use crate::redact_reqwest_error;
+
+/// Error returned by a transport, e.g. a network error.
+pub type TransportError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Future returned by [`AsyncTransport::get`].
+#[cfg(all(feature = "async_client", not(target_arch = "wasm32")))]
+pub type TransportFuture<'a> =
+    Pin<Box<dyn Future<Output = Result<HttpResponse, TransportError>> + Send + 'a>>;
+
+/// Future returned by [`AsyncTransport::get`], not `Send` on `wasm32` as browser requests are bound to their thread.
+#[cfg(all(feature = "async_client", target_arch = "wasm32"))]
+pub type TransportFuture<'a> =
+    Pin<Box<dyn Future<Output = Result<HttpResponse, TransportError>> + 'a>>;
+
+/// Status code and body of an HTTP response.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpResponse {
+    pub status: u16,
+    pub body: String,
+}
+
+/// Sends `GET` requests for [`crate::AsyncClient`].
+#[cfg(feature = "async_client")]
+pub trait AsyncTransport: std::fmt::Debug + Send + Sync {
+    fn get<'a>(&'a self, url: &'a str) -> TransportFuture<'a>;
+}
+
+#[cfg(all(feature = "async_client", feature = "reqwest"))]
+impl AsyncTransport for reqwest::Client {
+    fn get<'a>(&'a self, url: &'a str) -> TransportFuture<'a> {
+        Box::pin(async move {
+            let response = Self::get(self, url).send().await?;
+            let status = response.status().as_u16();
+            let body = response.text().await?;
+
+            Ok(HttpResponse { status, body })
+        })
+    }
+}
+
+/// Sends `GET` requests for [`crate::SyncClient`].
+#[cfg(feature = "sync_client")]
+pub trait SyncTransport: std::fmt::Debug + Send + Sync {
+    /// # Errors
+    /// Any error that prevents getting a response, e.g. a network error.
+    fn get(&self, url: &str) -> Result<HttpResponse, TransportError>;
+}
+
+#[cfg(all(feature = "sync_client", feature = "reqwest"))]
+impl SyncTransport for reqwest::blocking::Client {
+    fn get(&self, url: &str) -> Result<HttpResponse, TransportError> {
+        let response = Self::get(self, url).send()?;
+        let status = response.status().as_u16();
+        let body = response.text()?;
+
+        Ok(HttpResponse { status, body })
+    }
+}
+
+/// Transport returning queued responses instead of sending requests, handy to test code using the clients.
+///
+/// Clones share the queue and the requested URLs, so keep a clone to inspect them after passing it to a client.
+#[cfg(any(feature = "async_client", feature = "sync_client"))]
+#[derive(Debug, Clone, Default)]
+pub struct MockTransport {
+    responses: Arc<Mutex<VecDeque<HttpResponse>>>,
+    requests: Arc<Mutex<Vec<String>>>,
+}
+
+#[cfg(any(feature = "async_client", feature = "sync_client"))]
+impl MockTransport {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queues a response, responses are returned in the order they were queued.
+    #[must_use]
+    pub fn with_response(self, status: u16, body: impl Into<String>) -> Self {
+        self.responses
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push_back(HttpResponse {
+                status,
+                body: body.into(),
+            });
+        self
+    }
+
+    /// URLs requested so far, including the API key.
+    #[must_use]
+    pub fn requests(&self) -> Vec<String> {
+        self.requests
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    fn respond(&self, url: &str) -> Result<HttpResponse, TransportError> {
+        self.requests
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(url.to_string());
+
+        self.responses
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .pop_front()
+            .ok_or_else(|| "no response queued in MockTransport".into())
+    }
+}
+
+#[cfg(feature = "async_client")]
+impl AsyncTransport for MockTransport {
+    fn get<'a>(&'a self, url: &'a str) -> TransportFuture<'a> {
+        let response = self.respond(url);
+        Box::pin(async move { response })
+    }
+}
+
+#[cfg(feature = "sync_client")]
+impl SyncTransport for MockTransport {
+    fn get(&self, url: &str) -> Result<HttpResponse, TransportError> {
+        self.respond(url)
+    }
+}
+
+/// Converts a transport error, keeping `reqwest` errors as [`ApiError::ReqwestError`] with the API key redacted.
+#[cfg(all(
+    feature = "reqwest",
+    any(feature = "async_client", feature = "sync_client")
+))]
+pub(crate) fn into_api_error(err: TransportError, api_key: &str) -> ApiError {
+    match err.downcast::<reqwest::Error>() {
+        Ok(err) => ApiError::ReqwestError {
+            source: redact_reqwest_error(*err, api_key),
+        },
+        Err(err) => ApiError::TransportError { source: err },
+    }
+}
+
+/// Converts a transport error, there is no `reqwest` error to redact without the `reqwest` feature.
+#[cfg(all(
+    not(feature = "reqwest"),
+    any(feature = "async_client", feature = "sync_client")
+))]
+pub(crate) fn into_api_error(err: TransportError, _api_key: &str) -> ApiError {
+    ApiError::TransportError { source: err }
+}
+
+#[cfg(all(test, any(feature = "async_client", feature = "sync_client")))]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_mock_transport_returns_queued_responses() {
+        let transport = MockTransport::new()
+            .with_response(200, "first")
+            .with_response(429, "second");
+
+        assert_eq!(transport.respond("url1").unwrap().body, "first");
+        assert_eq!(transport.respond("url2").unwrap().status, 429);
+        assert!(transport.respond("url3").is_err());
+        assert_eq!(transport.requests(), ["url1", "url2", "url3"]);
+    }
+
+    #[test]
+    fn test_into_api_error_custom_error() {
+        let err = into_api_error("connection refused".into(), "test-api-key");
+
+        assert!(matches!(
+            err,
+            ApiError::TransportError { source } if source.to_string() == "connection refused"
+        ));
+    }
+
+    #[cfg(feature = "reqwest")]
+    #[test]
+    fn test_into_api_error_reqwest_error() {
+        let source = reqwest::Client::new().get("not a url").build().unwrap_err();
+        let err = into_api_error(source.into(), "test-api-key");
+
+        assert!(matches!(err, ApiError::ReqwestError { source } if source.is_builder()));
+    }
+}